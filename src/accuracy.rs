@@ -0,0 +1,100 @@
+use serde::Serialize;
+
+use crate::db::ForecastObservationPair;
+
+/// Upper bounds (inclusive, hours) of the lead time buckets. A pair with a
+/// lead time of 5h lands in the 6h bucket, 30h in the 48h bucket, etc.
+pub const LEAD_BUCKETS: &[i64] = &[1, 3, 6, 12, 24, 48, 72, 120, 168];
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ErrorStats {
+    pub count: usize,
+    /// Mean of forecast − observed. Positive means the forecast ran high.
+    pub bias: Option<f64>,
+    /// Mean absolute error.
+    pub mae: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadTimeStats {
+    pub lead_hours: i64,
+    pub temperature_c: ErrorStats,
+    pub wind_speed_ms: ErrorStats,
+    pub precipitation_mm: ErrorStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccuracyReport {
+    pub days: i64,
+    pub leads: Vec<LeadTimeStats>,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    count: usize,
+    sum: f64,
+    abs_sum: f64,
+}
+
+impl Accumulator {
+    fn add(&mut self, forecast: Option<f64>, observed: Option<f64>) {
+        if let (Some(f), Some(o)) = (forecast, observed) {
+            if f.is_finite() && o.is_finite() {
+                let err = f - o;
+                self.count += 1;
+                self.sum += err;
+                self.abs_sum += err.abs();
+            }
+        }
+    }
+
+    fn stats(&self) -> ErrorStats {
+        if self.count == 0 {
+            return ErrorStats::default();
+        }
+        let n = self.count as f64;
+        ErrorStats {
+            count: self.count,
+            bias: Some(self.sum / n),
+            mae: Some(self.abs_sum / n),
+        }
+    }
+}
+
+fn bucket_for(lead_hours: i64) -> Option<usize> {
+    if lead_hours <= 0 {
+        return None;
+    }
+    LEAD_BUCKETS.iter().position(|&b| lead_hours <= b)
+}
+
+/// Aggregate forecast/observation pairs into bias and MAE per lead time bucket.
+pub fn compute(pairs: &[ForecastObservationPair], days: i64) -> AccuracyReport {
+    let mut acc: Vec<[Accumulator; 3]> = LEAD_BUCKETS.iter().map(|_| Default::default()).collect();
+
+    for p in pairs {
+        let Some(idx) = bucket_for(p.lead_hours) else {
+            continue;
+        };
+        let [temp, wind, precip] = &mut acc[idx];
+        temp.add(
+            Some(p.forecast_temperature_c),
+            Some(p.observed_temperature_c),
+        );
+        wind.add(p.forecast_wind_speed_ms, p.observed_wind_speed_ms);
+        precip.add(p.forecast_precipitation_mm, p.observed_precipitation_mm);
+    }
+
+    let leads = LEAD_BUCKETS
+        .iter()
+        .zip(acc.iter())
+        .map(|(&lead_hours, [temp, wind, precip])| LeadTimeStats {
+            lead_hours,
+            temperature_c: temp.stats(),
+            wind_speed_ms: wind.stats(),
+            precipitation_mm: precip.stats(),
+        })
+        .collect();
+
+    AccuracyReport { days, leads }
+}
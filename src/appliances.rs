@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Days, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use tracing::{debug, info};
+
+use crate::{
+    cheapest_window,
+    config::Config,
+    db,
+    notify::{self, VapidConfig},
+};
+
+/// Hours before its deadline a run may start, so each job runs once a day.
+const WINDOW_HOURS: i64 = 24;
+/// Reminders not sent by this long after the start (e.g. while the app was
+/// down) are dropped rather than sent late.
+const LATE_MINUTES: i64 = 15;
+
+/// Deadlines are local times like `07:00`.
+pub fn parse_deadline(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+/// The first time `job` can be done by that leaves room for the whole run.
+pub fn next_deadline(job: &db::ApplianceJob, now: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+    let time = parse_deadline(&job.deadline)?;
+    let today = now.with_timezone(&tz).date_naive();
+    let earliest_end = now + Duration::minutes(job.duration_minutes);
+    (0..=2)
+        .filter_map(|days| {
+            let date = today.checked_add_days(Days::new(days))?;
+            tz.from_local_datetime(&date.and_time(time)).earliest()
+        })
+        .map(|dt| dt.to_utc())
+        .find(|deadline| *deadline >= earliest_end)
+}
+
+fn format_timestamp(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.to_utc())
+}
+
+/// Find the cheapest start for the next occurrence of every job whose
+/// deadline stored prices reach, i.e. once tomorrow's prices are out. A
+/// planned run is kept as is until the job is edited.
+async fn plan_runs(db: &db::Db, config: &Config, now: DateTime<Utc>) -> Result<()> {
+    let Some(covered_until) = db
+        .get_latest_electricity_timestamp()
+        .await?
+        .as_deref()
+        .and_then(parse_timestamp)
+        .map(|latest| latest + Duration::minutes(15))
+    else {
+        return Ok(());
+    };
+
+    for job in db.list_appliance_jobs().await? {
+        let Some(deadline) = next_deadline(&job, now, config.tz) else {
+            continue;
+        };
+        if deadline > covered_until {
+            continue;
+        }
+        let key = format_timestamp(deadline);
+        if db.get_appliance_run(job.id, &key).await?.is_some() {
+            continue;
+        }
+        // Starting from the next quarter hour leaves time for the reminder
+        let next_slot = DateTime::from_timestamp((now.timestamp() / 900 + 1) * 900, 0).unwrap();
+        let earliest = (deadline - Duration::hours(WINDOW_HOURS)).max(next_slot);
+        let duration = Duration::minutes(job.duration_minutes);
+        let Some(report) =
+            cheapest_window::find(db, config, duration, Some(earliest), Some(deadline), None)
+                .await?
+        else {
+            debug!("No prices cover {} before {key}", job.name);
+            continue;
+        };
+        let window = report.cheapest;
+        info!(
+            "Planned {} to start at {} ({:.2} c/kWh) to be done by {key}",
+            job.name, window.start, window.avg_price_cents_kwh
+        );
+        db.insert_appliance_run(&db::ApplianceRun {
+            job_id: job.id,
+            deadline: key,
+            start: format_timestamp(window.start),
+            end: format_timestamp(window.end),
+            avg_price_cents_kwh: window.avg_price_cents_kwh,
+            notified: false,
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Push a reminder for every planned run starting within the configured
+/// number of minutes.
+async fn send_reminders(db: &db::Db, config: &Config, now: DateTime<Utc>) -> Result<()> {
+    let lead = Duration::minutes(config.appliance_reminder_minutes);
+    let due: Vec<db::ApplianceRun> = db
+        .list_appliance_runs(&format_timestamp(now))
+        .await?
+        .into_iter()
+        .filter(|run| !run.notified)
+        .filter(|run| parse_timestamp(&run.start).is_some_and(|start| now >= start - lead))
+        .collect();
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let jobs: HashMap<i64, db::ApplianceJob> = db
+        .list_appliance_jobs()
+        .await?
+        .into_iter()
+        .map(|job| (job.id, job))
+        .collect();
+    let subscriptions = db.list_subscriptions().await?;
+    let vapid = VapidConfig::from_config(config);
+    let local = |s: &str| {
+        parse_timestamp(s)
+            .map(|dt| dt.with_timezone(&config.tz).format("%H:%M").to_string())
+            .unwrap_or_default()
+    };
+
+    for run in due {
+        db.mark_appliance_run_notified(run.job_id, &run.deadline)
+            .await?;
+        let Some(job) = jobs.get(&run.job_id) else {
+            continue;
+        };
+        let late = parse_timestamp(&run.start)
+            .is_some_and(|start| now > start + Duration::minutes(LATE_MINUTES));
+        if late {
+            info!(
+                "Skipping late reminder for {} starting {}",
+                job.name, run.start
+            );
+            continue;
+        }
+        let message = format!(
+            "{}: start at {}, done {} · {:.1} snt/kWh, {:.1} snt for {} kWh",
+            job.name,
+            local(&run.start),
+            local(&run.end),
+            run.avg_price_cents_kwh,
+            run.avg_price_cents_kwh * job.energy_kwh,
+            job.energy_kwh,
+        );
+        info!("Sending appliance reminder: {message}");
+        notify::send_all_logged(&subscriptions, &message, &vapid, "Appliance reminder").await;
+    }
+    Ok(())
+}
+
+/// Plan upcoming runs and send the reminders that are due.
+pub async fn run_check(db: &db::Db, config: &Config) -> Result<()> {
+    let now = Utc::now();
+    plan_runs(db, config, now).await?;
+    send_reminders(db, config, now).await
+}
@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use tracing::{error, info};
+
+use crate::{
+    config::Config,
+    db, degree_days,
+    weather::{Provider, WeatherProvider},
+};
+
+/// Pause between chunk queries so long backfills stay well inside FMI's
+/// request rate limits.
+const CHUNK_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How far back `weather backfill` goes when no `--from` is given. A year
+/// gives heating curve tuning every season; hours already stored are just
+/// overwritten.
+const DEFAULT_LOOKBACK_DAYS: i64 = 365;
+
+/// Fetch and store observations for `location` between `from` and `to`,
+/// walking backwards from `to` in chunks no longer than the provider allows
+/// per query. Returns the number of points stored.
+pub async fn backfill_observations(
+    db: &db::Db,
+    config: &Config,
+    location: &db::Location,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<usize> {
+    let provider = Provider::from_config(config);
+    let site = location.site(config.weather_provider);
+    let wind_site = location.wind_site(config.weather_provider);
+    let span = provider.max_observation_span();
+
+    let mut stored = 0;
+    let mut chunk_end = to;
+    while chunk_end > from {
+        let chunk_start = (chunk_end - span).max(from);
+        info!(
+            "Backfilling {} observations {chunk_start} .. {chunk_end}",
+            location.slug
+        );
+
+        let points = provider
+            .fetch_observations(site, chunk_start, chunk_end)
+            .await?;
+        db.upsert_weather_observations(location.id, &points).await?;
+        stored += points.len();
+
+        if let Some(wind_sid) = wind_site {
+            match provider
+                .fetch_observations(wind_sid, chunk_start, chunk_end)
+                .await
+            {
+                Ok(points) => {
+                    if let Err(e) = db.merge_wind_observations(location.id, &points).await {
+                        error!("Failed to merge wind observations: {e}");
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to fetch wind observations from {wind_sid}: {e}");
+                }
+            }
+        }
+
+        chunk_end = chunk_start;
+        if chunk_end > from {
+            tokio::time::sleep(CHUNK_DELAY).await;
+        }
+    }
+
+    if let Err(e) = degree_days::update(db, location, from, to).await {
+        error!("Failed to update degree days for {}: {e}", location.slug);
+    }
+
+    Ok(stored)
+}
+
+/// Where regular observation fetches for `location` pick up: the last stored
+/// observation, or a day back if there are none.
+pub async fn default_start(db: &db::Db, location: &db::Location) -> Result<DateTime<Utc>> {
+    let latest = db.get_latest_observation_timestamp(location.id).await?;
+    Ok(latest
+        .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+        .map(|dt| dt.to_utc())
+        .unwrap_or_else(|| Utc::now() - chrono::Duration::hours(24)))
+}
+
+/// `weather backfill [--location <slug>] [--from <YYYY-MM-DD>]`
+///
+/// Without `--location` every location is backfilled. Without `--from` each
+/// location is filled in over the last `DEFAULT_LOOKBACK_DAYS`, so history from
+/// before the app was deployed is fetched too.
+pub async fn run_cli(db: &db::Db, config: &Config, args: &[String]) -> Result<()> {
+    let mut slug = None;
+    let mut from_date = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--location" => {
+                slug = Some(args.next().context("--location needs a slug")?.clone());
+            }
+            "--from" => {
+                let value = args.next().context("--from needs a date")?;
+                from_date = Some(
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .context("--from must be a date like 2024-01-31")?,
+                );
+            }
+            other => return Err(anyhow!("Unknown backfill argument {other}")),
+        }
+    }
+
+    let locations = match &slug {
+        Some(slug) => vec![db
+            .get_location(slug)
+            .await?
+            .ok_or_else(|| anyhow!("Unknown location {slug}"))?],
+        None => db.list_locations().await?,
+    };
+
+    let now = Utc::now();
+    for location in &locations {
+        let from = match from_date {
+            Some(date) => {
+                let tz = location.tz();
+                tz.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .earliest()
+                    .context("--from date doesn't exist in the location's timezone")?
+                    .to_utc()
+            }
+            None => now - chrono::Duration::days(DEFAULT_LOOKBACK_DAYS),
+        };
+        let stored = backfill_observations(db, config, location, from, now).await?;
+        info!(
+            "Backfilled {stored} observation points for {}",
+            location.slug
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{config::Config, db};
+
+/// Length of an electricity price slot.
+const SLOT_MINUTES: i64 = 15;
+/// How far ahead windows are searched when no latest end is given.
+const DEFAULT_SEARCH_HOURS: i64 = 48;
+/// Longest run `parse_duration` accepts, as no search reaches further.
+pub const MAX_DURATION_HOURS: i64 = DEFAULT_SEARCH_HOURS;
+
+/// A 15-minute slot at its total price.
+#[derive(Debug, Clone, Copy)]
+pub struct Slot {
+    pub start: DateTime<Utc>,
+    pub price_cents_kwh: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Window {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Time-weighted mean of the slot prices the run covers.
+    pub avg_price_cents_kwh: f64,
+    /// Cost of the run at the given power, if one was given.
+    pub cost_cents: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowReport {
+    pub duration_minutes: i64,
+    pub cheapest: Window,
+    /// The same run started in the current slot, if prices cover it.
+    pub now: Option<Window>,
+    /// How much cheaper per kWh the cheapest start is than starting now.
+    pub savings_cents_kwh: Option<f64>,
+}
+
+/// Durations like `2h15m`, `6h`, `45m`, `1.5h` or `2:15`, up to
+/// `MAX_DURATION_HOURS`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim().to_lowercase();
+    let max_minutes = MAX_DURATION_HOURS * 60;
+    if let Some((h, m)) = s.split_once(':') {
+        let minutes = h
+            .trim()
+            .parse::<i64>()
+            .ok()?
+            .checked_mul(60)?
+            .checked_add(m.trim().parse::<i64>().ok()?)?;
+        return (1..=max_minutes)
+            .contains(&minutes)
+            .then(|| Duration::minutes(minutes));
+    }
+    let mut minutes = 0.0;
+    let mut number = String::new();
+    for c in s.chars() {
+        match c {
+            '0'..='9' | '.' | ',' => number.push(if c == ',' { '.' } else { c }),
+            'h' | 'm' => {
+                let value: f64 = number.parse().ok()?;
+                minutes += if c == 'h' { value * 60.0 } else { value };
+                number.clear();
+            }
+            ' ' => {}
+            _ => return None,
+        }
+    }
+    if !number.is_empty() {
+        // A bare number is minutes
+        minutes += number.parse::<f64>().ok()?;
+    }
+    // Checked before rounding, as the cast saturates
+    if !(minutes.is_finite() && minutes <= max_minutes as f64) {
+        return None;
+    }
+    let minutes = minutes.round() as i64;
+    (minutes > 0).then(|| Duration::minutes(minutes))
+}
+
+/// The inverse of `parse_duration`, e.g. `2h15m`.
+pub fn format_duration(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m}m"),
+        (h, 0) => format!("{h}h"),
+        (h, m) => format!("{h}h{m}m"),
+    }
+}
+
+/// `dt` rounded down to the start of its price slot.
+fn slot_floor(dt: DateTime<Utc>) -> DateTime<Utc> {
+    let step = SLOT_MINUTES * 60;
+    DateTime::from_timestamp(dt.timestamp() - dt.timestamp().rem_euclid(step), 0).unwrap()
+}
+
+/// The run of `duration` starting at `slots[first]`, or `None` if the
+/// slots it needs aren't all there.
+fn window_at(
+    slots: &[Slot],
+    first: usize,
+    duration: Duration,
+    power_kw: Option<f64>,
+) -> Option<Window> {
+    let start = slots[first].start;
+    let end = start + duration;
+    let mut covered = Duration::zero();
+    let mut weighted = 0.0;
+    for (k, slot) in slots[first..].iter().enumerate() {
+        if covered >= duration {
+            break;
+        }
+        if slot.start != start + Duration::minutes(SLOT_MINUTES * k as i64) {
+            return None;
+        }
+        let minutes = Duration::minutes(SLOT_MINUTES).min(duration - covered);
+        weighted += slot.price_cents_kwh * minutes.num_seconds() as f64;
+        covered += minutes;
+    }
+    if covered < duration {
+        return None;
+    }
+    let avg_price_cents_kwh = weighted / duration.num_seconds() as f64;
+    let hours = duration.num_seconds() as f64 / 3600.0;
+    Some(Window {
+        start,
+        end,
+        avg_price_cents_kwh,
+        cost_cents: power_kw.map(|kw| avg_price_cents_kwh * kw * hours),
+    })
+}
+
+/// Every contiguous run of `duration` that starts no earlier than the slot
+/// containing `earliest` and ends by `latest`.
+pub fn windows(
+    slots: &[Slot],
+    duration: Duration,
+    earliest: DateTime<Utc>,
+    latest: DateTime<Utc>,
+    power_kw: Option<f64>,
+) -> impl Iterator<Item = Window> + '_ {
+    let earliest = slot_floor(earliest);
+    (0..slots.len())
+        .filter(move |&i| slots[i].start >= earliest && slots[i].start + duration <= latest)
+        .filter_map(move |i| window_at(slots, i, duration, power_kw))
+}
+
+/// The cheapest of `windows`.
+pub fn cheapest(
+    slots: &[Slot],
+    duration: Duration,
+    earliest: DateTime<Utc>,
+    latest: DateTime<Utc>,
+    power_kw: Option<f64>,
+) -> Option<Window> {
+    windows(slots, duration, earliest, latest, power_kw)
+        .min_by(|a, b| a.avg_price_cents_kwh.total_cmp(&b.avg_price_cents_kwh))
+}
+
+/// Stored prices from `from` to `to` as total prices per slot.
+pub async fn load_slots(
+    db: &db::Db,
+    config: &Config,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Slot>> {
+    let prices = db
+        .get_electricity_prices(
+            &slot_floor(from).format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            &to.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        )
+        .await?;
+    Ok(prices
+        .iter()
+        .filter_map(|p| {
+            let start = DateTime::parse_from_rfc3339(&p.timestamp).ok()?.to_utc();
+            Some(Slot {
+                start,
+                price_cents_kwh: config
+                    .price_components
+                    .total(p.price_cents_kwh, start, config.tz),
+            })
+        })
+        .collect())
+}
+
+/// Find the cheapest start for a run of `duration` between `earliest` and
+/// `latest` (default: two days ahead), compared with starting now. `None`
+/// when the run doesn't fit or no stored prices cover it.
+pub async fn find(
+    db: &db::Db,
+    config: &Config,
+    duration: Duration,
+    earliest: Option<DateTime<Utc>>,
+    latest: Option<DateTime<Utc>>,
+    power_kw: Option<f64>,
+) -> Result<Option<WindowReport>> {
+    let now = Utc::now();
+    let earliest = earliest.unwrap_or(now).max(now);
+    let latest = latest.unwrap_or(now + Duration::hours(DEFAULT_SEARCH_HOURS));
+    // Times far out of range can't fit a run at all
+    if earliest
+        .checked_add_signed(duration)
+        .is_none_or(|end| end > latest)
+    {
+        return Ok(None);
+    }
+    let slots = load_slots(db, config, now, latest.max(now + duration)).await?;
+    let Some(cheapest) = cheapest(&slots, duration, earliest, latest, power_kw) else {
+        return Ok(None);
+    };
+    let current = slot_floor(now);
+    let now_window = slots
+        .iter()
+        .position(|s| s.start == current)
+        .and_then(|i| window_at(&slots, i, duration, power_kw));
+    Ok(Some(WindowReport {
+        duration_minutes: duration.num_minutes(),
+        savings_cents_kwh: now_window
+            .as_ref()
+            .map(|w| w.avg_price_cents_kwh - cheapest.avg_price_cents_kwh),
+        cheapest,
+        now: now_window,
+    }))
+}
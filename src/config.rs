@@ -1,24 +1,190 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono_tz::Tz;
 
+use crate::{
+    db::NewLocation,
+    electricity::{self, PriceComponents},
+    heating::TemperatureAdjustment,
+    price_alerts::PriceAlerts,
+    weather,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeatherProviderKind {
+    Fmi,
+    OpenMeteo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElectricityProviderKind {
+    Porssisahko,
+    Entsoe,
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
-    pub fmi_sid: String,
+    pub weather_provider: WeatherProviderKind,
+    pub location_name: String,
+    pub fmi_sid: Option<String>,
     pub fmi_sid_wind: Option<String>,
+    pub fmi_wfs_url: String,
+    pub place: Option<String>,
+    pub latlon: Option<String>,
+    pub open_meteo_url: String,
+    pub open_meteo_archive_url: String,
+    pub electricity_provider: ElectricityProviderKind,
+    pub porssisahko_url: String,
+    pub entsoe_url: String,
+    pub entsoe_token: Option<String>,
+    /// EIC code of the ENTSO-E bidding zone.
+    pub entsoe_area: String,
     pub port: u16,
     pub db_path: String,
     pub vapid_subject: String,
     pub vapid_public_key: String,
     pub vapid_private_key: String,
+    /// Bearer token indoor sensors post readings with. Ingestion is disabled
+    /// when unset.
+    pub sensor_token: Option<String>,
     pub summary_hour: u32,
+    /// Degrees past a curve breakpoint before a different setting is recommended.
+    pub heating_deadband_c: f64,
+    /// Hours a radiator setting is held before a change is recommended.
+    pub radiator_min_dwell_hours: i64,
+    /// Degrees the effective temperature drops per m/s of wind.
+    pub wind_chill_c_per_ms: f64,
+    /// Degrees the effective temperature rises for a clear daytime hour.
+    pub solar_gain_c: f64,
+    /// Indoor temperature the heating curve is tuned for. When set, a curve
+    /// proposal is fitted from indoor readings once a day.
+    pub indoor_target_c: Option<f64>,
+    /// Margin, transfer and tax added to spot prices for the total price.
+    pub price_components: PriceComponents,
+    /// Hours the house holds heat. When set, heat is shifted from expensive
+    /// hours into cheap ones at most this many hours before them.
+    pub thermal_mass_hours: Option<usize>,
+    /// Thresholds that trigger a notification when new prices come in.
+    pub price_alerts: PriceAlerts,
+    /// Minutes before an appliance job's planned start its reminder is sent.
+    pub appliance_reminder_minutes: i64,
+    /// Broker to publish state to. MQTT is disabled when `MQTT_HOST` is unset.
+    pub mqtt: Option<MqttConfig>,
     pub tz: Tz,
 }
 
+#[derive(Clone, Debug)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub client_id: String,
+    /// Prefix for the state topics, e.g. `weather/temperature`.
+    pub topic_prefix: String,
+    /// Home Assistant discovery prefix.
+    pub discovery_prefix: String,
+}
+
+/// A price component in c/kWh, zero when unset.
+fn cents_from_env(name: &str) -> Result<f64> {
+    std::env::var(name)
+        .unwrap_or_else(|_| "0".to_string())
+        .parse()
+        .with_context(|| format!("{name} must be a number of cents per kWh"))
+}
+
+impl MqttConfig {
+    fn from_env() -> Result<Option<Self>> {
+        let Some(host) = std::env::var("MQTT_HOST").ok().filter(|h| !h.is_empty()) else {
+            return Ok(None);
+        };
+        Ok(Some(MqttConfig {
+            host,
+            port: std::env::var("MQTT_PORT")
+                .unwrap_or_else(|_| "1883".to_string())
+                .parse()
+                .context("MQTT_PORT must be a valid port number")?,
+            username: std::env::var("MQTT_USERNAME")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            password: std::env::var("MQTT_PASSWORD")
+                .ok()
+                .filter(|p| !p.is_empty()),
+            client_id: std::env::var("MQTT_CLIENT_ID").unwrap_or_else(|_| "weather".to_string()),
+            topic_prefix: std::env::var("MQTT_TOPIC_PREFIX")
+                .unwrap_or_else(|_| "weather".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            discovery_prefix: std::env::var("MQTT_DISCOVERY_PREFIX")
+                .unwrap_or_else(|_| "homeassistant".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        }))
+    }
+}
+
 impl Config {
     pub fn from_env() -> Result<Self> {
+        let weather_provider = match std::env::var("WEATHER_PROVIDER").as_deref() {
+            Err(_) | Ok("fmi") => WeatherProviderKind::Fmi,
+            Ok("open-meteo") => WeatherProviderKind::OpenMeteo,
+            Ok(other) => {
+                return Err(anyhow!(
+                    "WEATHER_PROVIDER must be `fmi` or `open-meteo`, got `{other}`"
+                ))
+            }
+        };
+        let place = std::env::var("PLACE").ok();
+        let latlon = std::env::var("LATLON").ok();
+        // Without any way to locate the weather, fall back to Helsinki Kaisaniemi.
+        let fmi_sid = match std::env::var("FMI_SID") {
+            Ok(sid) => Some(sid),
+            Err(_) if place.is_none() && latlon.is_none() => Some("101799".to_string()),
+            Err(_) => None,
+        };
+        if weather_provider == WeatherProviderKind::OpenMeteo && latlon.is_none() {
+            return Err(anyhow!(
+                "LATLON (e.g. 60.17,24.94) is required for open-meteo"
+            ));
+        }
+        let electricity_provider = match std::env::var("ELECTRICITY_PROVIDER").as_deref() {
+            Err(_) | Ok("porssisahko") => ElectricityProviderKind::Porssisahko,
+            Ok("entsoe") => ElectricityProviderKind::Entsoe,
+            Ok(other) => {
+                return Err(anyhow!(
+                    "ELECTRICITY_PROVIDER must be `porssisahko` or `entsoe`, got `{other}`"
+                ))
+            }
+        };
+        let entsoe_token = std::env::var("ENTSOE_TOKEN").ok().filter(|t| !t.is_empty());
+        if electricity_provider == ElectricityProviderKind::Entsoe && entsoe_token.is_none() {
+            return Err(anyhow!("ENTSOE_TOKEN is required for entsoe"));
+        }
+
         Ok(Config {
-            fmi_sid: std::env::var("FMI_SID").unwrap_or_else(|_| "101799".to_string()),
+            weather_provider,
+            location_name: std::env::var("LOCATION_NAME")
+                .ok()
+                .or_else(|| place.clone())
+                .unwrap_or_else(|| "Home".to_string()),
+            fmi_sid,
             fmi_sid_wind: std::env::var("FMI_SID_WIND").ok(),
+            fmi_wfs_url: std::env::var("FMI_WFS_URL")
+                .unwrap_or_else(|_| weather::fmi::DEFAULT_WFS_URL.to_string()),
+            place,
+            latlon,
+            open_meteo_url: std::env::var("OPEN_METEO_URL")
+                .unwrap_or_else(|_| weather::open_meteo::DEFAULT_FORECAST_URL.to_string()),
+            open_meteo_archive_url: std::env::var("OPEN_METEO_ARCHIVE_URL")
+                .unwrap_or_else(|_| weather::open_meteo::DEFAULT_ARCHIVE_URL.to_string()),
+            electricity_provider,
+            porssisahko_url: std::env::var("PORSSISAHKO_URL")
+                .unwrap_or_else(|_| electricity::porssisahko::DEFAULT_URL.to_string()),
+            entsoe_url: std::env::var("ENTSOE_URL")
+                .unwrap_or_else(|_| electricity::entsoe::DEFAULT_URL.to_string()),
+            entsoe_token,
+            entsoe_area: std::env::var("ENTSOE_AREA")
+                .unwrap_or_else(|_| electricity::entsoe::DEFAULT_AREA.to_string()),
             port: std::env::var("PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
@@ -28,14 +194,91 @@ impl Config {
                 .unwrap_or_else(|_| "mailto:security@veetik.com".to_string()),
             vapid_public_key: std::env::var("VAPID_PUBLIC_KEY").unwrap_or_default(),
             vapid_private_key: std::env::var("VAPID_PRIVATE_KEY").unwrap_or_default(),
+            sensor_token: std::env::var("SENSOR_TOKEN").ok().filter(|t| !t.is_empty()),
             summary_hour: std::env::var("SUMMARY_HOUR")
                 .unwrap_or_else(|_| "7".to_string())
                 .parse()
                 .context("SUMMARY_HOUR must be a number 0-23")?,
+            heating_deadband_c: std::env::var("HEATING_DEADBAND_C")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .context("HEATING_DEADBAND_C must be a number")?,
+            radiator_min_dwell_hours: std::env::var("RADIATOR_MIN_DWELL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .context("RADIATOR_MIN_DWELL_HOURS must be a whole number of hours")?,
+            wind_chill_c_per_ms: std::env::var("WIND_CHILL_C_PER_MS")
+                .unwrap_or_else(|_| "0.5".to_string())
+                .parse()
+                .context("WIND_CHILL_C_PER_MS must be a number")?,
+            solar_gain_c: std::env::var("SOLAR_GAIN_C")
+                .unwrap_or_else(|_| "2.0".to_string())
+                .parse()
+                .context("SOLAR_GAIN_C must be a number")?,
+            indoor_target_c: std::env::var("INDOOR_TARGET_C")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()
+                .context("INDOOR_TARGET_C must be a number")?,
+            price_components: PriceComponents {
+                margin_cents: cents_from_env("SELLER_MARGIN_CENTS")?,
+                transfer_day_cents: cents_from_env("TRANSFER_DAY_CENTS")?,
+                transfer_night_cents: cents_from_env("TRANSFER_NIGHT_CENTS")?,
+                tax_cents: cents_from_env("ELECTRICITY_TAX_CENTS")?,
+                vat_percent: std::env::var("VAT_PERCENT")
+                    .unwrap_or_else(|_| "25.5".to_string())
+                    .parse()
+                    .context("VAT_PERCENT must be a number")?,
+            },
+            thermal_mass_hours: std::env::var("THERMAL_MASS_HOURS")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()
+                .context("THERMAL_MASS_HOURS must be a whole number of hours")?,
+            price_alerts: PriceAlerts {
+                below_cents: std::env::var("PRICE_ALERT_BELOW_CENTS")
+                    .ok()
+                    .map(|v| v.parse())
+                    .transpose()
+                    .context("PRICE_ALERT_BELOW_CENTS must be a number of cents per kWh")?,
+                negative: std::env::var("PRICE_ALERT_NEGATIVE")
+                    .map(|v| v == "true" || v == "1")
+                    .unwrap_or(false),
+                peak_above_cents: std::env::var("PRICE_ALERT_PEAK_CENTS")
+                    .ok()
+                    .map(|v| v.parse())
+                    .transpose()
+                    .context("PRICE_ALERT_PEAK_CENTS must be a number of cents per kWh")?,
+            },
+            appliance_reminder_minutes: std::env::var("APPLIANCE_REMINDER_MINUTES")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .context("APPLIANCE_REMINDER_MINUTES must be a whole number of minutes")?,
+            mqtt: MqttConfig::from_env()?,
             tz: std::env::var("TZ")
                 .unwrap_or_else(|_| "Europe/Helsinki".to_string())
                 .parse()
                 .context("TZ must be a valid IANA timezone (e.g. Europe/Helsinki)")?,
         })
     }
+
+    pub fn temperature_adjustment(&self) -> TemperatureAdjustment {
+        TemperatureAdjustment {
+            wind_c_per_ms: self.wind_chill_c_per_ms,
+            solar_gain_c: self.solar_gain_c,
+        }
+    }
+
+    /// The location described by the environment, used to seed an empty
+    /// `locations` table.
+    pub fn seed_location(&self) -> NewLocation {
+        NewLocation::new(
+            &self.location_name,
+            self.fmi_sid.clone(),
+            self.fmi_sid_wind.clone(),
+            self.place.clone(),
+            self.latlon.clone(),
+            self.tz.name(),
+        )
+    }
 }
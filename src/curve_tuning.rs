@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    db::{self, NewCurveProposal},
+    heating::{CurveStep, HeatingCurve},
+};
+
+/// Hours of outdoor observations averaged for each sample. The house reacts
+/// slowly, so the indoor temperature follows the recent past rather than
+/// the current hour.
+const OUTDOOR_WINDOW_HOURS: i64 = 24;
+/// Fewer outdoor observations than this in the window and the sample is skipped.
+const MIN_OUTDOOR_HOURS: usize = 12;
+/// Fitting on less than a few days of hourly samples isn't worth proposing.
+pub const MIN_SAMPLES: usize = 72;
+
+/// One hour of indoor temperature with what drove it.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub indoor_c: f64,
+    /// Mean outdoor temperature over the preceding day.
+    pub outdoor_c: f64,
+    /// Mean setting of the radiators at the time.
+    pub setting: f64,
+}
+
+/// Linear model `indoor = intercept + outdoor_coef * outdoor + setting_coef * setting`.
+#[derive(Debug, Clone, Copy)]
+pub struct Model {
+    pub intercept: f64,
+    pub outdoor_coef: f64,
+    pub setting_coef: f64,
+    pub samples: usize,
+    pub r_squared: f64,
+}
+
+impl Model {
+    pub fn predict(&self, outdoor_c: f64, setting: f64) -> f64 {
+        self.intercept + self.outdoor_coef * outdoor_c + self.setting_coef * setting
+    }
+
+    /// Lowest outdoor temperature at which `setting` still holds `target_c` indoors.
+    pub fn balance_temperature(&self, target_c: f64, setting: f64) -> f64 {
+        (target_c - self.intercept - self.setting_coef * setting) / self.outdoor_coef
+    }
+}
+
+/// Least-squares fit of the model. `None` if the samples can't separate the
+/// effect of the dial from the weather, e.g. the setting never changed.
+pub fn fit(samples: &[Sample]) -> Option<Model> {
+    if samples.len() < 3 {
+        return None;
+    }
+
+    // Normal equations XᵀX·β = Xᵀy with X = [1, outdoor, setting]
+    let mut xtx = [[0.0_f64; 3]; 3];
+    let mut xty = [0.0_f64; 3];
+    for s in samples {
+        let x = [1.0, s.outdoor_c, s.setting];
+        for i in 0..3 {
+            for j in 0..3 {
+                xtx[i][j] += x[i] * x[j];
+            }
+            xty[i] += x[i] * s.indoor_c;
+        }
+    }
+    let [intercept, outdoor_coef, setting_coef] = solve3(xtx, xty)?;
+
+    let mean = samples.iter().map(|s| s.indoor_c).sum::<f64>() / samples.len() as f64;
+    let (mut ss_res, mut ss_tot) = (0.0, 0.0);
+    for s in samples {
+        let predicted = intercept + outdoor_coef * s.outdoor_c + setting_coef * s.setting;
+        ss_res += (s.indoor_c - predicted).powi(2);
+        ss_tot += (s.indoor_c - mean).powi(2);
+    }
+    let r_squared = if ss_tot > 0.0 {
+        1.0 - ss_res / ss_tot
+    } else {
+        0.0
+    };
+
+    Some(Model {
+        intercept,
+        outdoor_coef,
+        setting_coef,
+        samples: samples.len(),
+        r_squared,
+    })
+}
+
+/// Gaussian elimination with partial pivoting.
+fn solve3(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in col + 1..3 {
+            let factor = a[row][col] / pivot_row[col];
+            for (k, v) in a[row].iter_mut().enumerate().skip(col) {
+                *v -= factor * pivot_row[k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let sum: f64 = (row + 1..3).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Breakpoints that switch to the next dial position of `current` exactly
+/// where the model says the previous one stops holding `target_c`.
+pub fn propose_curve(model: &Model, current: &HeatingCurve, target_c: f64) -> Result<HeatingCurve> {
+    if model.outdoor_coef <= 0.0 {
+        return Err(anyhow!(
+            "Indoor temperature doesn't rise with outdoor temperature in the data"
+        ));
+    }
+    if model.setting_coef <= 0.0 {
+        return Err(anyhow!(
+            "Indoor temperature doesn't rise with the radiator setting in the data"
+        ));
+    }
+
+    let settings = current.settings();
+    let steps = settings
+        .windows(2)
+        .map(|w| CurveStep {
+            below_c: (model.balance_temperature(target_c, w[0]) * 10.0).round() / 10.0,
+            setting: w[1],
+        })
+        .collect();
+    HeatingCurve::new(steps, settings[0])
+}
+
+/// Pair each hour of indoor readings at `location` in `from..to` with the
+/// outdoor temperature before it and the radiator settings at the time.
+pub async fn collect_samples(
+    db: &db::Db,
+    location: &db::Location,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Sample>> {
+    let fmt = |dt: DateTime<Utc>| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let indoor = db
+        .get_indoor_hourly(location.id, &fmt(from), &fmt(to))
+        .await?;
+    let observations: Vec<(DateTime<Utc>, f64)> = db
+        .get_weather_observations(
+            location.id,
+            &fmt(from - Duration::hours(OUTDOOR_WINDOW_HOURS)),
+            &fmt(to),
+        )
+        .await?
+        .into_iter()
+        .filter(|o| o.temperature_c.is_finite())
+        .filter_map(|o| {
+            let ts = DateTime::parse_from_rfc3339(&o.timestamp).ok()?.to_utc();
+            Some((ts, o.temperature_c))
+        })
+        .collect();
+    // The settings in effect at `from`, then the changes after it
+    let mut changes = db.get_radiator_settings_at(&fmt(from)).await?;
+    changes.extend(db.get_radiator_history(&fmt(from), &fmt(to)).await?);
+    let changes: Vec<(DateTime<Utc>, i64, f64)> = changes
+        .into_iter()
+        .filter_map(|c| {
+            let ts = DateTime::parse_from_rfc3339(&c.changed_at).ok()?.to_utc();
+            Some((ts, c.radiator_id, c.setting))
+        })
+        .collect();
+
+    // Indoor hours, changes and observations are all in time order, so each
+    // is walked once: changes up to the hour are applied, and the outdoor
+    // window slides along the observations with a running sum.
+    let mut in_effect: HashMap<i64, f64> = HashMap::new();
+    let mut next_change = 0;
+    let (mut window_first, mut window_end) = (0, 0);
+    let mut window_sum = 0.0;
+    let mut samples = Vec::new();
+    for (hour, indoor_c) in indoor {
+        let Ok(hour) = DateTime::parse_from_rfc3339(&hour).map(|h| h.to_utc()) else {
+            continue;
+        };
+        while let Some(&(_, radiator_id, setting)) =
+            changes.get(next_change).filter(|(ts, _, _)| *ts <= hour)
+        {
+            in_effect.insert(radiator_id, setting);
+            next_change += 1;
+        }
+        let window_start = hour - Duration::hours(OUTDOOR_WINDOW_HOURS);
+        while let Some(&(_, t)) = observations.get(window_end).filter(|(ts, _)| *ts <= hour) {
+            window_sum += t;
+            window_end += 1;
+        }
+        while let Some(&(_, t)) = observations[..window_end]
+            .get(window_first)
+            .filter(|(ts, _)| *ts <= window_start)
+        {
+            window_sum -= t;
+            window_first += 1;
+        }
+
+        // With several radiators the house sees their mean setting
+        if in_effect.is_empty() {
+            continue;
+        }
+        let setting = in_effect.values().sum::<f64>() / in_effect.len() as f64;
+        let window_len = window_end - window_first;
+        if window_len < MIN_OUTDOOR_HOURS {
+            continue;
+        }
+        samples.push(Sample {
+            indoor_c,
+            outdoor_c: window_sum / window_len as f64,
+            setting,
+        });
+    }
+    Ok(samples)
+}
+
+/// Fit the model on the last `days` days at `location` and store the
+/// resulting curve as a pending proposal. Returns the proposal id, or `None`
+/// if the fitted curve is the one already in use or, with `skip_seen`, the
+/// one in the newest pending or rejected proposal, so the daily proposal
+/// doesn't bring back a curve the user has already turned down.
+pub async fn create_proposal(
+    db: &db::Db,
+    location: &db::Location,
+    target_c: f64,
+    days: i64,
+    skip_seen: bool,
+) -> Result<Option<i64>> {
+    let now = Utc::now();
+    let samples = collect_samples(db, location, now - Duration::days(days), now).await?;
+    if samples.len() < MIN_SAMPLES {
+        return Err(anyhow!(
+            "Only {} usable hours of indoor data, need at least {MIN_SAMPLES}",
+            samples.len()
+        ));
+    }
+    let model = fit(&samples).ok_or_else(|| {
+        anyhow!("Can't fit a model, the radiator setting needs to have varied in the data")
+    })?;
+    let current = db.get_heating_curve().await?;
+    let curve = propose_curve(&model, &current, target_c)?;
+    if curve == current {
+        tracing::info!("Fitted heating curve matches the current one, nothing to propose");
+        return Ok(None);
+    }
+    if skip_seen {
+        if let Some(seen) = db.get_latest_open_curve_proposal().await? {
+            if seen.curve == curve {
+                tracing::info!(
+                    "Fitted heating curve matches proposal {} ({}), not proposing it again",
+                    seen.id,
+                    seen.status
+                );
+                return Ok(None);
+            }
+        }
+    }
+
+    let id = db
+        .insert_curve_proposal(&NewCurveProposal {
+            target_c,
+            model,
+            curve,
+        })
+        .await?;
+    tracing::info!(
+        "Proposed heating curve {id}: indoor = {:.2} + {:.3}·outdoor + {:.2}·setting (n={}, R²={:.2})",
+        model.intercept,
+        model.outdoor_coef,
+        model.setting_coef,
+        model.samples,
+        model.r_squared
+    );
+    Ok(Some(id))
+}
@@ -1,8 +1,14 @@
 use anyhow::Result;
 use chrono::NaiveDate;
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use chrono_tz::Tz;
+use sqlx::{sqlite::SqlitePoolOptions, Connection, SqlitePool};
 
-use crate::weather::ForecastPoint;
+use crate::{
+    config::WeatherProviderKind,
+    curve_tuning::Model,
+    heating::{CurveStep, HeatingCurve, SettingSource},
+    weather::{ForecastPoint, ForecastRun},
+};
 
 #[derive(Clone)]
 pub struct Db {
@@ -44,10 +50,77 @@ impl Db {
         .await?;
 
         sqlx::query(
-            "CREATE TABLE IF NOT EXISTS radiator_setting (
-                id          INTEGER PRIMARY KEY CHECK (id = 1),
+            "CREATE TABLE IF NOT EXISTS radiators (
+                id             INTEGER PRIMARY KEY,
+                slug           TEXT NOT NULL UNIQUE,
+                name           TEXT NOT NULL,
+                curve_offset_c REAL NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        // Settings stored before radiators had names belong to radiator 1.
+        sqlx::query(
+            "INSERT INTO radiators (id, slug, name)
+             SELECT 1, 'radiator', 'Radiator' WHERE NOT EXISTS (SELECT 1 FROM radiators)",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(RADIATOR_SETTING_TABLE).execute(&pool).await?;
+        migrate_radiator_setting(&pool).await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS radiator_setting_history (
+                id          INTEGER PRIMARY KEY,
+                radiator_id INTEGER NOT NULL DEFAULT 1,
                 setting     REAL NOT NULL,
-                updated_at  TEXT NOT NULL
+                changed_at  TEXT NOT NULL,
+                source      TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        add_column_if_missing(
+            &pool,
+            "radiator_setting_history",
+            "radiator_id",
+            "INTEGER NOT NULL DEFAULT 1",
+        )
+        .await?;
+
+        // Before the history existed the web form was the only way to change
+        // the setting, so the stored one is recorded as such.
+        sqlx::query(
+            "INSERT INTO radiator_setting_history (radiator_id, setting, changed_at, source)
+             SELECT radiator_id, setting, updated_at, 'web' FROM radiator_setting
+             WHERE NOT EXISTS (SELECT 1 FROM radiator_setting_history)",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS heating_curve (
+                below_c REAL,
+                setting REAL NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS heating_curve_proposals (
+                id           INTEGER PRIMARY KEY,
+                created_at   TEXT NOT NULL,
+                target_c     REAL NOT NULL,
+                samples      INTEGER NOT NULL,
+                r_squared    REAL NOT NULL,
+                intercept    REAL NOT NULL,
+                outdoor_coef REAL NOT NULL,
+                setting_coef REAL NOT NULL,
+                curve        TEXT NOT NULL,
+                status       TEXT NOT NULL DEFAULT 'pending'
             )",
         )
         .execute(&pool)
@@ -63,21 +136,221 @@ impl Db {
         .await?;
 
         sqlx::query(
-            "CREATE TABLE IF NOT EXISTS weather_observations (
-                timestamp        TEXT NOT NULL PRIMARY KEY,
+            "CREATE TABLE IF NOT EXISTS appliance_jobs (
+                id               INTEGER PRIMARY KEY,
+                name             TEXT NOT NULL,
+                duration_minutes INTEGER NOT NULL,
+                deadline         TEXT NOT NULL,
+                energy_kwh       REAL NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS appliance_runs (
+                job_id              INTEGER NOT NULL REFERENCES appliance_jobs(id) ON DELETE CASCADE,
+                deadline            TEXT NOT NULL,
+                start               TEXT NOT NULL,
+                end                 TEXT NOT NULL,
+                avg_price_cents_kwh REAL NOT NULL,
+                notified            INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (job_id, deadline)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS preheat_hours (
+                radiator_id INTEGER NOT NULL,
+                timestamp   TEXT NOT NULL,
+                base        REAL NOT NULL,
+                setting     REAL NOT NULL,
+                shift       TEXT NOT NULL,
+                PRIMARY KEY (radiator_id, timestamp)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS locations (
+                id          INTEGER PRIMARY KEY,
+                slug        TEXT NOT NULL UNIQUE,
+                name        TEXT NOT NULL,
+                fmisid      TEXT NOT NULL,
+                fmisid_wind TEXT,
+                latlon      TEXT,
+                timezone    TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sensors (
+                id          TEXT PRIMARY KEY,
+                location_id INTEGER NOT NULL REFERENCES locations(id) ON DELETE CASCADE
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sensor_readings (
+                sensor_id     TEXT NOT NULL REFERENCES sensors(id) ON DELETE CASCADE,
+                timestamp     TEXT NOT NULL,
+                temperature_c REAL NOT NULL,
+                humidity      REAL,
+                PRIMARY KEY (sensor_id, timestamp)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(WEATHER_OBSERVATIONS_TABLE)
+            .execute(&pool)
+            .await?;
+        sqlx::query(FORECAST_SNAPSHOTS_TABLE).execute(&pool).await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS degree_days (
+                location_id INTEGER NOT NULL REFERENCES locations(id) ON DELETE CASCADE,
+                date        TEXT NOT NULL,
+                mean_temp_c REAL NOT NULL,
+                hours       INTEGER NOT NULL,
+                hdd         REAL NOT NULL,
+                PRIMARY KEY (location_id, date)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS forecast_points (
+                snapshot_id      INTEGER NOT NULL REFERENCES forecast_snapshots(id) ON DELETE CASCADE,
+                timestamp        TEXT NOT NULL,
                 temperature_c    REAL NOT NULL,
                 wind_speed_ms    REAL,
                 precipitation_mm REAL,
                 humidity         REAL,
-                wind_direction   REAL
+                wind_direction   REAL,
+                PRIMARY KEY (snapshot_id, timestamp)
             )",
         )
         .execute(&pool)
         .await?;
 
+        for table in ["weather_observations", "forecast_points"] {
+            for (column, decl) in [
+                ("cloud_cover", "REAL"),
+                ("pressure_hpa", "REAL"),
+                ("dew_point_c", "REAL"),
+                ("wind_gust_ms", "REAL"),
+                ("weather_symbol", "REAL"),
+            ] {
+                add_column_if_missing(&pool, table, column, decl).await?;
+            }
+        }
+        add_column_if_missing(&pool, "locations", "place", "TEXT").await?;
+        add_column_if_missing(&pool, "locations", "resolved_name", "TEXT").await?;
+
+        migrate_to_locations(
+            &pool,
+            "weather_observations",
+            WEATHER_OBSERVATIONS_TABLE,
+            "timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction, cloud_cover, pressure_hpa, dew_point_c, wind_gust_ms, weather_symbol",
+        )
+        .await?;
+        migrate_to_locations(
+            &pool,
+            "forecast_snapshots",
+            FORECAST_SNAPSHOTS_TABLE,
+            "id, issued_at, fetched_at",
+        )
+        .await?;
+
         Ok(Self::new(pool))
     }
 
+    // --- Locations ---
+
+    /// Insert the location described by `Config` as location 1 if there are
+    /// no locations yet. Rows from before locations existed belong to it.
+    pub async fn seed_location(&self, location: &NewLocation) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO locations (id, slug, name, fmisid, fmisid_wind, place, latlon, timezone)
+             SELECT 1, ?, ?, ?, ?, ?, ?, ? WHERE NOT EXISTS (SELECT 1 FROM locations)",
+        )
+        .bind(&location.slug)
+        .bind(&location.name)
+        .bind(&location.fmisid)
+        .bind(&location.fmisid_wind)
+        .bind(&location.place)
+        .bind(&location.latlon)
+        .bind(&location.timezone)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn insert_location(&self, location: &NewLocation) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO locations (slug, name, fmisid, fmisid_wind, place, latlon, timezone) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&location.slug)
+        .bind(&location.name)
+        .bind(&location.fmisid)
+        .bind(&location.fmisid_wind)
+        .bind(&location.place)
+        .bind(&location.latlon)
+        .bind(&location.timezone)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_locations(&self) -> Result<Vec<Location>> {
+        let rows = sqlx::query_as::<_, Location>(
+            "SELECT id, slug, name, fmisid, fmisid_wind, place, latlon, timezone, resolved_name FROM locations ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get_location(&self, slug: &str) -> Result<Option<Location>> {
+        let row = sqlx::query_as::<_, Location>(
+            "SELECT id, slug, name, fmisid, fmisid_wind, place, latlon, timezone, resolved_name FROM locations WHERE slug = ?",
+        )
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Remember the station/place name the weather provider resolved a location to.
+    pub async fn set_location_resolved_name(&self, location_id: i64, name: &str) -> Result<()> {
+        sqlx::query("UPDATE locations SET resolved_name = ? WHERE id = ?")
+            .bind(name)
+            .bind(location_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The home location: the first one configured. Radiator advice and the
+    /// daily summary are based on it.
+    pub async fn default_location(&self) -> Result<Location> {
+        let row = sqlx::query_as::<_, Location>(
+            "SELECT id, slug, name, fmisid, fmisid_wind, place, latlon, timezone, resolved_name FROM locations ORDER BY id LIMIT 1",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
     // --- Subscriptions ---
 
     pub async fn insert_subscription(
@@ -136,29 +409,298 @@ impl Db {
         Ok(())
     }
 
-    // --- Radiator setting ---
+    // --- Radiators ---
+
+    pub async fn list_radiators(&self) -> Result<Vec<Radiator>> {
+        let rows = sqlx::query_as::<_, Radiator>(
+            "SELECT id, slug, name, curve_offset_c FROM radiators ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get_radiator(&self, id: i64) -> Result<Option<Radiator>> {
+        let row = sqlx::query_as::<_, Radiator>(
+            "SELECT id, slug, name, curve_offset_c FROM radiators WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    pub async fn insert_radiator(&self, name: &str, curve_offset_c: f64) -> Result<()> {
+        sqlx::query("INSERT INTO radiators (slug, name, curve_offset_c) VALUES (?, ?, ?)")
+            .bind(slugify(name))
+            .bind(name)
+            .bind(curve_offset_c)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_radiator(&self, id: i64, name: &str, curve_offset_c: f64) -> Result<()> {
+        sqlx::query("UPDATE radiators SET name = ?, curve_offset_c = ? WHERE id = ?")
+            .bind(name)
+            .bind(curve_offset_c)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // --- Radiator settings ---
 
-    pub async fn get_radiator_setting(&self) -> Result<Option<f64>> {
+    pub async fn get_radiator_setting(&self, radiator_id: i64) -> Result<Option<f64>> {
         let row: Option<(f64,)> =
-            sqlx::query_as("SELECT setting FROM radiator_setting WHERE id = 1")
+            sqlx::query_as("SELECT setting FROM radiator_setting WHERE radiator_id = ?")
+                .bind(radiator_id)
                 .fetch_optional(&self.pool)
                 .await?;
         Ok(row.map(|r| r.0))
     }
 
-    pub async fn set_radiator_setting(&self, setting: f64) -> Result<()> {
-        let now = chrono::Utc::now().to_rfc3339();
+    /// The stored setting with the time it was last changed to its current value.
+    pub async fn get_radiator_state(&self, radiator_id: i64) -> Result<Option<RadiatorState>> {
+        let row = sqlx::query_as::<_, RadiatorState>(
+            "SELECT setting, updated_at FROM radiator_setting WHERE radiator_id = ?",
+        )
+        .bind(radiator_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Store the dial setting and record the change in the history.
+    /// Re-submitting the current setting changes nothing, so it doesn't
+    /// restart its dwell time or add a history entry.
+    pub async fn set_radiator_setting(
+        &self,
+        radiator_id: i64,
+        setting: f64,
+        source: SettingSource,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let mut tx = self.pool.begin().await?;
+        let current: Option<(f64,)> =
+            sqlx::query_as("SELECT setting FROM radiator_setting WHERE radiator_id = ?")
+                .bind(radiator_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if current.is_some_and(|(c,)| c == setting) {
+            return Ok(());
+        }
+        sqlx::query(
+            "INSERT INTO radiator_setting (radiator_id, setting, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(radiator_id) DO UPDATE SET setting = excluded.setting, updated_at = excluded.updated_at",
+        )
+        .bind(radiator_id)
+        .bind(setting)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
         sqlx::query(
-            "INSERT INTO radiator_setting (id, setting, updated_at) VALUES (1, ?, ?)
-             ON CONFLICT(id) DO UPDATE SET setting = excluded.setting, updated_at = excluded.updated_at",
+            "INSERT INTO radiator_setting_history (radiator_id, setting, changed_at, source) VALUES (?, ?, ?, ?)",
         )
+        .bind(radiator_id)
         .bind(setting)
         .bind(&now)
+        .bind(source.as_str())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Setting changes of every radiator in `from..to`, oldest first.
+    pub async fn get_radiator_history(&self, from: &str, to: &str) -> Result<Vec<RadiatorChange>> {
+        let rows = sqlx::query_as::<_, RadiatorChange>(
+            "SELECT h.radiator_id, r.slug AS radiator, h.setting, h.changed_at, h.source
+             FROM radiator_setting_history h JOIN radiators r ON r.id = h.radiator_id
+             WHERE h.changed_at >= ? AND h.changed_at < ? ORDER BY h.changed_at",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Each radiator's last change before `at`, i.e. the settings in effect
+    /// then.
+    pub async fn get_radiator_settings_at(&self, at: &str) -> Result<Vec<RadiatorChange>> {
+        let rows = sqlx::query_as::<_, RadiatorChange>(
+            "SELECT h.radiator_id, r.slug AS radiator, h.setting, h.changed_at, h.source
+             FROM radiator_setting_history h JOIN radiators r ON r.id = h.radiator_id
+             WHERE h.changed_at = (
+                 SELECT MAX(changed_at) FROM radiator_setting_history
+                 WHERE radiator_id = h.radiator_id AND changed_at < ?
+             )
+             ORDER BY h.changed_at",
+        )
+        .bind(at)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    // --- Heating curve ---
+
+    /// The stored heating curve, or the built-in default if none is saved.
+    /// A row with a NULL `below_c` holds the setting above every breakpoint.
+    pub async fn get_heating_curve(&self) -> Result<HeatingCurve> {
+        let rows: Vec<(Option<f64>, f64)> =
+            sqlx::query_as("SELECT below_c, setting FROM heating_curve")
+                .fetch_all(&self.pool)
+                .await?;
+        if rows.is_empty() {
+            return Ok(HeatingCurve::default());
+        }
+        let otherwise = rows
+            .iter()
+            .find(|(below_c, _)| below_c.is_none())
+            .map(|(_, setting)| *setting)
+            .unwrap_or(0.0);
+        let steps = rows
+            .iter()
+            .filter_map(|(below_c, setting)| {
+                below_c.map(|below_c| CurveStep {
+                    below_c,
+                    setting: *setting,
+                })
+            })
+            .collect();
+        HeatingCurve::new(steps, otherwise)
+    }
+
+    pub async fn set_heating_curve(&self, curve: &HeatingCurve) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM heating_curve")
+            .execute(&mut *tx)
+            .await?;
+        for step in &curve.steps {
+            sqlx::query("INSERT INTO heating_curve (below_c, setting) VALUES (?, ?)")
+                .bind(step.below_c)
+                .bind(step.setting)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("INSERT INTO heating_curve (below_c, setting) VALUES (NULL, ?)")
+            .bind(curve.otherwise)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Store a proposed curve as pending, superseding any earlier pending one.
+    pub async fn insert_curve_proposal(&self, proposal: &NewCurveProposal) -> Result<i64> {
+        let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE heating_curve_proposals SET status = 'superseded' WHERE status = 'pending'",
+        )
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query(
+            "INSERT INTO heating_curve_proposals (created_at, target_c, samples, r_squared, intercept, outdoor_coef, setting_coef, curve)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&now)
+        .bind(proposal.target_c)
+        .bind(proposal.model.samples as i64)
+        .bind(proposal.model.r_squared)
+        .bind(proposal.model.intercept)
+        .bind(proposal.model.outdoor_coef)
+        .bind(proposal.model.setting_coef)
+        .bind(serde_json::to_string(&proposal.curve)?)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get_pending_curve_proposal(&self) -> Result<Option<CurveProposal>> {
+        let row = sqlx::query_as::<_, CurveProposalRow>(
+            "SELECT id, created_at, target_c, samples, r_squared, intercept, outdoor_coef, setting_coef, curve
+             FROM heating_curve_proposals WHERE status = 'pending' ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(CurveProposalRow::into_proposal).transpose()
+    }
+
+    /// The newest proposal that is pending or was rejected.
+    pub async fn get_latest_open_curve_proposal(&self) -> Result<Option<SeenCurveProposal>> {
+        let row = sqlx::query_as::<_, (i64, String, String)>(
+            "SELECT id, status, curve FROM heating_curve_proposals
+             WHERE status IN ('pending', 'rejected') ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|(id, status, curve)| {
+            Ok(SeenCurveProposal {
+                id,
+                status,
+                curve: serde_json::from_str(&curve)?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Mark a pending proposal `approved` or `rejected`. Returns the proposal
+    /// if it was still pending.
+    pub async fn resolve_curve_proposal(
+        &self,
+        id: i64,
+        status: &str,
+    ) -> Result<Option<CurveProposal>> {
+        let row = sqlx::query_as::<_, CurveProposalRow>(
+            "UPDATE heating_curve_proposals SET status = ? WHERE id = ? AND status = 'pending'
+             RETURNING id, created_at, target_c, samples, r_squared, intercept, outdoor_coef, setting_coef, curve",
+        )
+        .bind(status)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(CurveProposalRow::into_proposal).transpose()
+    }
+
+    // --- Preheating ---
+
+    /// Record a shifted hour the scheduler acted on. The first record of an
+    /// hour is kept, so later replanning can't rewrite what was sent.
+    pub async fn insert_preheat_hour(&self, hour: &PreheatHour) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO preheat_hours (radiator_id, timestamp, base, setting, shift) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(hour.radiator_id)
+        .bind(&hour.timestamp)
+        .bind(hour.base)
+        .bind(hour.setting)
+        .bind(&hour.shift)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
+    /// Shifted hours of `radiator_id` from `from` on, oldest first.
+    pub async fn list_preheat_hours(
+        &self,
+        radiator_id: i64,
+        from: &str,
+    ) -> Result<Vec<PreheatHour>> {
+        let rows = sqlx::query_as::<_, PreheatHour>(
+            "SELECT radiator_id, timestamp, base, setting, shift FROM preheat_hours WHERE radiator_id = ? AND timestamp >= ? ORDER BY timestamp",
+        )
+        .bind(radiator_id)
+        .bind(from)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
     // --- Electricity prices ---
 
     pub async fn upsert_electricity_prices(&self, prices: &[(String, f64)]) -> Result<()> {
@@ -198,21 +740,142 @@ impl Db {
         .await?;
         Ok(rows)
     }
+
+    // --- Appliance jobs ---
+
+    pub async fn list_appliance_jobs(&self) -> Result<Vec<ApplianceJob>> {
+        let rows = sqlx::query_as::<_, ApplianceJob>(
+            "SELECT id, name, duration_minutes, deadline, energy_kwh FROM appliance_jobs ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn insert_appliance_job(&self, job: &NewApplianceJob) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO appliance_jobs (name, duration_minutes, deadline, energy_kwh) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&job.name)
+        .bind(job.duration_minutes)
+        .bind(&job.deadline)
+        .bind(job.energy_kwh)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Update a job. Runs planned for it that haven't been reminded of yet
+    /// are dropped so they're planned again with the new details.
+    pub async fn update_appliance_job(&self, id: i64, job: &NewApplianceJob) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE appliance_jobs SET name = ?, duration_minutes = ?, deadline = ?, energy_kwh = ? WHERE id = ?",
+        )
+        .bind(&job.name)
+        .bind(job.duration_minutes)
+        .bind(&job.deadline)
+        .bind(job.energy_kwh)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM appliance_runs WHERE job_id = ? AND notified = 0")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete_appliance_job(&self, id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM appliance_runs WHERE job_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM appliance_jobs WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_appliance_run(
+        &self,
+        job_id: i64,
+        deadline: &str,
+    ) -> Result<Option<ApplianceRun>> {
+        let row = sqlx::query_as::<_, ApplianceRun>(
+            "SELECT job_id, deadline, start, end, avg_price_cents_kwh, notified FROM appliance_runs WHERE job_id = ? AND deadline = ?",
+        )
+        .bind(job_id)
+        .bind(deadline)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    pub async fn insert_appliance_run(&self, run: &ApplianceRun) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO appliance_runs (job_id, deadline, start, end, avg_price_cents_kwh, notified) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(run.job_id)
+        .bind(&run.deadline)
+        .bind(&run.start)
+        .bind(&run.end)
+        .bind(run.avg_price_cents_kwh)
+        .bind(run.notified)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Planned runs ending at or after `from`, earliest start first.
+    pub async fn list_appliance_runs(&self, from: &str) -> Result<Vec<ApplianceRun>> {
+        let rows = sqlx::query_as::<_, ApplianceRun>(
+            "SELECT job_id, deadline, start, end, avg_price_cents_kwh, notified FROM appliance_runs WHERE end >= ? ORDER BY start",
+        )
+        .bind(from)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn mark_appliance_run_notified(&self, job_id: i64, deadline: &str) -> Result<()> {
+        sqlx::query("UPDATE appliance_runs SET notified = 1 WHERE job_id = ? AND deadline = ?")
+            .bind(job_id)
+            .bind(deadline)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     // --- Weather observations ---
 
-    pub async fn upsert_weather_observations(&self, points: &[ForecastPoint]) -> Result<()> {
+    pub async fn upsert_weather_observations(
+        &self,
+        location_id: i64,
+        points: &[ForecastPoint],
+    ) -> Result<()> {
         let mut tx = self.pool.begin().await?;
         for p in points {
             let ts = p.timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string();
             sqlx::query(
-                "INSERT OR REPLACE INTO weather_observations (timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction) VALUES (?, ?, ?, ?, ?, ?)",
+                "INSERT OR REPLACE INTO weather_observations (location_id, timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction, cloud_cover, pressure_hpa, dew_point_c, wind_gust_ms, weather_symbol) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             )
+            .bind(location_id)
             .bind(&ts)
             .bind(p.temperature_c)
             .bind(finite_or_none(p.wind_speed_ms))
             .bind(finite_or_none(p.precipitation_mm))
             .bind(finite_or_none(p.humidity))
             .bind(finite_or_none(p.wind_direction))
+            .bind(finite_or_none(p.cloud_cover))
+            .bind(finite_or_none(p.pressure_hpa))
+            .bind(finite_or_none(p.dew_point_c))
+            .bind(finite_or_none(p.wind_gust_ms))
+            .bind(finite_or_none(p.weather_symbol))
             .execute(&mut *tx)
             .await?;
         }
@@ -222,18 +885,25 @@ impl Db {
 
     /// Merge wind data from a secondary station into existing observations.
     /// Only updates wind columns for timestamps that already exist.
-    pub async fn merge_wind_observations(&self, points: &[ForecastPoint]) -> Result<()> {
+    pub async fn merge_wind_observations(
+        &self,
+        location_id: i64,
+        points: &[ForecastPoint],
+    ) -> Result<()> {
         let mut tx = self.pool.begin().await?;
         for p in points {
             let ts = p.timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string();
             let ws = finite_or_none(p.wind_speed_ms);
             let wd = finite_or_none(p.wind_direction);
-            if ws.is_some() || wd.is_some() {
+            let wg = finite_or_none(p.wind_gust_ms);
+            if ws.is_some() || wd.is_some() || wg.is_some() {
                 sqlx::query(
-                    "UPDATE weather_observations SET wind_speed_ms = COALESCE(?, wind_speed_ms), wind_direction = COALESCE(?, wind_direction) WHERE timestamp = ?",
+                    "UPDATE weather_observations SET wind_speed_ms = COALESCE(?, wind_speed_ms), wind_direction = COALESCE(?, wind_direction), wind_gust_ms = COALESCE(?, wind_gust_ms) WHERE location_id = ? AND timestamp = ?",
                 )
                 .bind(ws)
                 .bind(wd)
+                .bind(wg)
+                .bind(location_id)
                 .bind(&ts)
                 .execute(&mut *tx)
                 .await?;
@@ -243,9 +913,13 @@ impl Db {
         Ok(())
     }
 
-    pub async fn get_latest_observation_timestamp(&self) -> Result<Option<String>> {
+    pub async fn get_latest_observation_timestamp(
+        &self,
+        location_id: i64,
+    ) -> Result<Option<String>> {
         let row: Option<(String,)> =
-            sqlx::query_as("SELECT MAX(timestamp) FROM weather_observations")
+            sqlx::query_as("SELECT MAX(timestamp) FROM weather_observations WHERE location_id = ?")
+                .bind(location_id)
                 .fetch_optional(&self.pool)
                 .await?;
         Ok(row.and_then(|r| if r.0.is_empty() { None } else { Some(r.0) }))
@@ -253,18 +927,245 @@ impl Db {
 
     pub async fn get_weather_observations(
         &self,
+        location_id: i64,
         from: &str,
         to: &str,
     ) -> Result<Vec<WeatherObservation>> {
         let rows = sqlx::query_as::<_, WeatherObservation>(
-            "SELECT timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction FROM weather_observations WHERE timestamp >= ? AND timestamp < ? ORDER BY timestamp",
+            "SELECT timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction, cloud_cover, pressure_hpa, dew_point_c, wind_gust_ms, weather_symbol FROM weather_observations WHERE location_id = ? AND timestamp >= ? AND timestamp < ? ORDER BY timestamp",
         )
+        .bind(location_id)
         .bind(from)
         .bind(to)
         .fetch_all(&self.pool)
         .await?;
         Ok(rows)
     }
+
+    // --- Indoor sensors ---
+
+    /// Register `sensor_id` at `location_id`, or move it there if it's known.
+    pub async fn upsert_sensor(&self, sensor_id: &str, location_id: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sensors (id, location_id) VALUES (?, ?)
+             ON CONFLICT(id) DO UPDATE SET location_id = excluded.location_id",
+        )
+        .bind(sensor_id)
+        .bind(location_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_sensor_location_id(&self, sensor_id: &str) -> Result<Option<i64>> {
+        let row: Option<(i64,)> = sqlx::query_as("SELECT location_id FROM sensors WHERE id = ?")
+            .bind(sensor_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0))
+    }
+
+    pub async fn insert_sensor_readings(
+        &self,
+        sensor_id: &str,
+        readings: &[SensorReading],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for r in readings {
+            sqlx::query(
+                "INSERT OR REPLACE INTO sensor_readings (sensor_id, timestamp, temperature_c, humidity) VALUES (?, ?, ?, ?)",
+            )
+            .bind(sensor_id)
+            .bind(&r.timestamp)
+            .bind(r.temperature_c)
+            .bind(r.humidity)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Mean indoor temperature per hour across the location's sensors, keyed
+    /// by the hour's `%Y-%m-%dT%H:00:00Z` timestamp.
+    pub async fn get_indoor_hourly(
+        &self,
+        location_id: i64,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<(String, f64)>> {
+        let rows = sqlx::query_as(
+            "SELECT strftime('%Y-%m-%dT%H:00:00Z', r.timestamp) AS hour, AVG(r.temperature_c)
+             FROM sensor_readings r JOIN sensors s ON s.id = r.sensor_id
+             WHERE s.location_id = ? AND r.timestamp >= ? AND r.timestamp < ?
+             GROUP BY hour ORDER BY hour",
+        )
+        .bind(location_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Mean of each of the location's sensors' latest reading since `since`.
+    pub async fn get_current_indoor_temperature(
+        &self,
+        location_id: i64,
+        since: &str,
+    ) -> Result<Option<f64>> {
+        let row: Option<(Option<f64>,)> = sqlx::query_as(
+            "SELECT AVG(r.temperature_c)
+             FROM sensor_readings r JOIN sensors s ON s.id = r.sensor_id
+             WHERE s.location_id = ?
+               AND r.timestamp = (SELECT MAX(timestamp) FROM sensor_readings WHERE sensor_id = r.sensor_id AND timestamp >= ?)",
+        )
+        .bind(location_id)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.and_then(|r| r.0))
+    }
+
+    // --- Degree days ---
+
+    pub async fn upsert_degree_days(&self, location_id: i64, days: &[DegreeDay]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for day in days {
+            sqlx::query(
+                "INSERT OR REPLACE INTO degree_days (location_id, date, mean_temp_c, hours, hdd) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(location_id)
+            .bind(&day.date)
+            .bind(day.mean_temp_c)
+            .bind(day.hours)
+            .bind(day.hdd)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_degree_days(&self, location_id: i64) -> Result<Vec<DegreeDay>> {
+        let rows = sqlx::query_as::<_, DegreeDay>(
+            "SELECT date, mean_temp_c, hours, hdd FROM degree_days WHERE location_id = ? ORDER BY date",
+        )
+        .bind(location_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    // --- Forecast snapshots ---
+
+    /// Every stored forecast point from `from` onwards that has a matching
+    /// observation, with its lead time relative to the run's issue time.
+    pub async fn get_forecast_observation_pairs(
+        &self,
+        from: &str,
+    ) -> Result<Vec<ForecastObservationPair>> {
+        let rows = sqlx::query_as::<_, ForecastObservationPair>(
+            "SELECT
+                (CAST(strftime('%s', p.timestamp) AS INTEGER) - CAST(strftime('%s', s.issued_at) AS INTEGER)) / 3600 AS lead_hours,
+                p.temperature_c    AS forecast_temperature_c,
+                o.temperature_c    AS observed_temperature_c,
+                p.wind_speed_ms    AS forecast_wind_speed_ms,
+                o.wind_speed_ms    AS observed_wind_speed_ms,
+                p.precipitation_mm AS forecast_precipitation_mm,
+                o.precipitation_mm AS observed_precipitation_mm
+             FROM forecast_points p
+             JOIN forecast_snapshots s ON s.id = p.snapshot_id
+             JOIN weather_observations o ON o.location_id = s.location_id AND o.timestamp = p.timestamp
+             WHERE p.timestamp >= ? AND p.timestamp > s.issued_at",
+        )
+        .bind(from)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Store a forecast run. Returns `false` if a run with the same issue time
+    /// was already stored, in which case nothing is written.
+    pub async fn insert_forecast_snapshot(
+        &self,
+        location_id: i64,
+        run: &ForecastRun,
+    ) -> Result<bool> {
+        let issued_at = run.issued_at.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let fetched_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO forecast_snapshots (location_id, issued_at, fetched_at) VALUES (?, ?, ?)",
+        )
+        .bind(location_id)
+        .bind(&issued_at)
+        .bind(&fetched_at)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        let snapshot_id = result.last_insert_rowid();
+
+        for p in &run.points {
+            let ts = p.timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            sqlx::query(
+                "INSERT OR REPLACE INTO forecast_points (snapshot_id, timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction, cloud_cover, pressure_hpa, dew_point_c, wind_gust_ms, weather_symbol) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(snapshot_id)
+            .bind(&ts)
+            .bind(p.temperature_c)
+            .bind(finite_or_none(p.wind_speed_ms))
+            .bind(finite_or_none(p.precipitation_mm))
+            .bind(finite_or_none(p.humidity))
+            .bind(finite_or_none(p.wind_direction))
+            .bind(finite_or_none(p.cloud_cover))
+            .bind(finite_or_none(p.pressure_hpa))
+            .bind(finite_or_none(p.dew_point_c))
+            .bind(finite_or_none(p.wind_gust_ms))
+            .bind(finite_or_none(p.weather_symbol))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Latest stored forecast run, with points from `from` onwards.
+    pub async fn get_latest_forecast_snapshot(
+        &self,
+        location_id: i64,
+        from: &str,
+    ) -> Result<Option<ForecastRun>> {
+        let snapshot: Option<(i64, String)> = sqlx::query_as(
+            "SELECT id, issued_at FROM forecast_snapshots WHERE location_id = ? ORDER BY issued_at DESC LIMIT 1",
+        )
+        .bind(location_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((snapshot_id, issued_at)) = snapshot else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query_as::<_, ForecastPointRow>(
+            "SELECT timestamp, temperature_c, wind_speed_ms, precipitation_mm, humidity, wind_direction, cloud_cover, pressure_hpa, dew_point_c, wind_gust_ms, weather_symbol FROM forecast_points WHERE snapshot_id = ? AND timestamp >= ? ORDER BY timestamp",
+        )
+        .bind(snapshot_id)
+        .bind(from)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(ForecastRun {
+            issued_at: chrono::DateTime::parse_from_rfc3339(&issued_at)?.to_utc(),
+            place_name: None,
+            points: rows
+                .into_iter()
+                .filter_map(ForecastPointRow::into_point)
+                .collect(),
+        }))
+    }
 }
 
 // --- Types ---
@@ -276,22 +1177,445 @@ pub struct Subscription {
     pub auth: String,
 }
 
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Location {
+    pub id: i64,
+    pub slug: String,
+    pub name: String,
+    /// Empty when the location is given by place name or coordinates instead.
+    pub fmisid: String,
+    pub fmisid_wind: Option<String>,
+    pub place: Option<String>,
+    pub latlon: Option<String>,
+    pub timezone: String,
+    /// Station/place name reported back by the weather provider.
+    pub resolved_name: Option<String>,
+}
+
+impl Location {
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::Europe__Helsinki)
+    }
+
+    /// Name to show for the place itself: what the provider resolved it to,
+    /// or the user's name for it until that's known.
+    pub fn place_label(&self) -> &str {
+        self.resolved_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Site identifier for the given weather provider. FMI takes an FMISID,
+    /// place name or coordinates, in that order of preference.
+    pub fn site(&self, provider: WeatherProviderKind) -> &str {
+        match provider {
+            WeatherProviderKind::Fmi if !self.fmisid.is_empty() => &self.fmisid,
+            WeatherProviderKind::Fmi => self
+                .place
+                .as_deref()
+                .or(self.latlon.as_deref())
+                .unwrap_or_default(),
+            WeatherProviderKind::OpenMeteo => self.latlon.as_deref().unwrap_or_default(),
+        }
+    }
+
+    /// Secondary station for wind observations. Only meaningful for FMI.
+    pub fn wind_site(&self, provider: WeatherProviderKind) -> Option<&str> {
+        match provider {
+            WeatherProviderKind::Fmi => self.fmisid_wind.as_deref(),
+            WeatherProviderKind::OpenMeteo => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewLocation {
+    pub slug: String,
+    pub name: String,
+    pub fmisid: String,
+    pub fmisid_wind: Option<String>,
+    pub place: Option<String>,
+    pub latlon: Option<String>,
+    pub timezone: String,
+}
+
+impl NewLocation {
+    pub fn new(
+        name: &str,
+        fmisid: Option<String>,
+        fmisid_wind: Option<String>,
+        place: Option<String>,
+        latlon: Option<String>,
+        timezone: &str,
+    ) -> Self {
+        Self {
+            slug: slugify(name),
+            name: name.to_string(),
+            fmisid: fmisid.unwrap_or_default(),
+            fmisid_wind,
+            place,
+            latlon,
+            timezone: timezone.to_string(),
+        }
+    }
+}
+
+/// Lowercase ASCII slug for `/l/{slug}` URLs, e.g. "Summer Cottage" → "summer-cottage".
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'ä' | 'å' => 'a',
+            'ö' => 'o',
+            c => c,
+        };
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "location".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RadiatorState {
+    pub setting: f64,
+    pub updated_at: String,
+}
+
+pub struct NewCurveProposal {
+    pub target_c: f64,
+    pub model: Model,
+    pub curve: HeatingCurve,
+}
+
+#[derive(Debug, Clone)]
+pub struct CurveProposal {
+    pub id: i64,
+    pub created_at: String,
+    pub target_c: f64,
+    pub model: Model,
+    pub curve: HeatingCurve,
+}
+
+/// A proposal's curve and what became of it.
+pub struct SeenCurveProposal {
+    pub id: i64,
+    /// `pending` or `rejected`.
+    pub status: String,
+    pub curve: HeatingCurve,
+}
+
+#[derive(sqlx::FromRow)]
+struct CurveProposalRow {
+    id: i64,
+    created_at: String,
+    target_c: f64,
+    samples: i64,
+    r_squared: f64,
+    intercept: f64,
+    outdoor_coef: f64,
+    setting_coef: f64,
+    curve: String,
+}
+
+impl CurveProposalRow {
+    fn into_proposal(self) -> Result<CurveProposal> {
+        Ok(CurveProposal {
+            id: self.id,
+            created_at: self.created_at,
+            target_c: self.target_c,
+            model: Model {
+                intercept: self.intercept,
+                outdoor_coef: self.outdoor_coef,
+                setting_coef: self.setting_coef,
+                samples: self.samples as usize,
+                r_squared: self.r_squared,
+            },
+            curve: serde_json::from_str(&self.curve)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SensorReading {
+    pub timestamp: String,
+    pub temperature_c: f64,
+    pub humidity: Option<f64>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Radiator {
+    pub id: i64,
+    pub slug: String,
+    pub name: String,
+    /// Degrees taken off the outdoor temperature before looking up the curve.
+    /// Positive for rooms that run cold, negative for sunny or warm ones.
+    pub curve_offset_c: f64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize)]
+pub struct RadiatorChange {
+    pub radiator_id: i64,
+    /// Slug of the radiator.
+    pub radiator: String,
+    pub setting: f64,
+    pub changed_at: String,
+    /// `web`, `notification`, `automation` or `mqtt`, see `SettingSource`.
+    pub source: String,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct ElectricityPrice {
     pub timestamp: String,
     pub price_cents_kwh: f64,
 }
 
+/// An hour the preheating plan shifted, as the scheduler acted on it.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PreheatHour {
+    pub radiator_id: i64,
+    pub timestamp: String,
+    pub base: f64,
+    pub setting: f64,
+    /// `raise` or `lower`, see `preheat::Shift`.
+    pub shift: String,
+}
+
+/// A recurring appliance run, e.g. the dishwasher done by 07:00 every day.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApplianceJob {
+    pub id: i64,
+    pub name: String,
+    pub duration_minutes: i64,
+    /// Local time the run has to be done by, `HH:MM`.
+    pub deadline: String,
+    pub energy_kwh: f64,
+}
+
+pub struct NewApplianceJob {
+    pub name: String,
+    pub duration_minutes: i64,
+    pub deadline: String,
+    pub energy_kwh: f64,
+}
+
+/// The cheapest start found for one occurrence of a job. Times are UTC.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApplianceRun {
+    pub job_id: i64,
+    pub deadline: String,
+    pub start: String,
+    pub end: String,
+    pub avg_price_cents_kwh: f64,
+    /// Whether the reminder has been sent.
+    pub notified: bool,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct WeatherObservation {
     pub timestamp: String,
     pub temperature_c: f64,
-    pub wind_speed_ms: f64,
-    pub precipitation_mm: f64,
-    pub humidity: f64,
-    pub wind_direction: f64,
+    pub wind_speed_ms: Option<f64>,
+    pub precipitation_mm: Option<f64>,
+    pub humidity: Option<f64>,
+    pub wind_direction: Option<f64>,
+    pub cloud_cover: Option<f64>,
+    pub pressure_hpa: Option<f64>,
+    pub dew_point_c: Option<f64>,
+    pub wind_gust_ms: Option<f64>,
+    pub weather_symbol: Option<f64>,
+}
+
+/// Daily heating degree day aggregate. `date` is the local calendar day
+/// (`%Y-%m-%d`) in the location's timezone.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DegreeDay {
+    pub date: String,
+    pub mean_temp_c: f64,
+    pub hours: i64,
+    pub hdd: f64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ForecastObservationPair {
+    pub lead_hours: i64,
+    pub forecast_temperature_c: f64,
+    pub observed_temperature_c: f64,
+    pub forecast_wind_speed_ms: Option<f64>,
+    pub observed_wind_speed_ms: Option<f64>,
+    pub forecast_precipitation_mm: Option<f64>,
+    pub observed_precipitation_mm: Option<f64>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ForecastPointRow {
+    timestamp: String,
+    temperature_c: f64,
+    wind_speed_ms: Option<f64>,
+    precipitation_mm: Option<f64>,
+    humidity: Option<f64>,
+    wind_direction: Option<f64>,
+    cloud_cover: Option<f64>,
+    pressure_hpa: Option<f64>,
+    dew_point_c: Option<f64>,
+    wind_gust_ms: Option<f64>,
+    weather_symbol: Option<f64>,
+}
+
+impl ForecastPointRow {
+    fn into_point(self) -> Option<ForecastPoint> {
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()?
+            .to_utc();
+        Some(ForecastPoint {
+            timestamp,
+            temperature_c: self.temperature_c,
+            wind_speed_ms: self.wind_speed_ms.unwrap_or(f64::NAN),
+            precipitation_mm: self.precipitation_mm.unwrap_or(f64::NAN),
+            humidity: self.humidity.unwrap_or(f64::NAN),
+            wind_direction: self.wind_direction.unwrap_or(f64::NAN),
+            cloud_cover: self.cloud_cover.unwrap_or(f64::NAN),
+            pressure_hpa: self.pressure_hpa.unwrap_or(f64::NAN),
+            dew_point_c: self.dew_point_c.unwrap_or(f64::NAN),
+            wind_gust_ms: self.wind_gust_ms.unwrap_or(f64::NAN),
+            weather_symbol: self.weather_symbol.unwrap_or(f64::NAN),
+        })
+    }
+}
+
+const RADIATOR_SETTING_TABLE: &str = "CREATE TABLE IF NOT EXISTS radiator_setting (
+    radiator_id INTEGER PRIMARY KEY REFERENCES radiators(id) ON DELETE CASCADE,
+    setting     REAL NOT NULL,
+    updated_at  TEXT NOT NULL
+)";
+
+const WEATHER_OBSERVATIONS_TABLE: &str = "CREATE TABLE IF NOT EXISTS weather_observations (
+    location_id      INTEGER NOT NULL REFERENCES locations(id) ON DELETE CASCADE,
+    timestamp        TEXT NOT NULL,
+    temperature_c    REAL NOT NULL,
+    wind_speed_ms    REAL,
+    precipitation_mm REAL,
+    humidity         REAL,
+    wind_direction   REAL,
+    cloud_cover      REAL,
+    pressure_hpa     REAL,
+    dew_point_c      REAL,
+    wind_gust_ms     REAL,
+    weather_symbol   REAL,
+    PRIMARY KEY (location_id, timestamp)
+)";
+
+const FORECAST_SNAPSHOTS_TABLE: &str = "CREATE TABLE IF NOT EXISTS forecast_snapshots (
+    id          INTEGER PRIMARY KEY,
+    location_id INTEGER NOT NULL REFERENCES locations(id) ON DELETE CASCADE,
+    issued_at   TEXT NOT NULL,
+    fetched_at  TEXT NOT NULL,
+    UNIQUE(location_id, issued_at)
+)";
+
+async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> Result<bool> {
+    let columns: Vec<(String,)> =
+        sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{table}')"))
+            .fetch_all(pool)
+            .await?;
+    Ok(columns.iter().any(|(name,)| name == column))
+}
+
+/// Rebuild a table created before locations existed so it is keyed by
+/// location, assigning every existing row to location 1. SQLite can't alter
+/// primary keys or unique constraints in place, hence the copy.
+async fn migrate_to_locations(
+    pool: &SqlitePool,
+    table: &str,
+    create_sql: &str,
+    columns: &str,
+) -> Result<()> {
+    if has_column(pool, table, "location_id").await? {
+        return Ok(());
+    }
+
+    let new_table = format!("{table}_new");
+    let mut conn = pool.acquire().await?;
+    // Dropping the old forecast_snapshots would otherwise cascade into forecast_points.
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await?;
+
+    let mut tx = conn.begin().await?;
+    sqlx::query(&create_sql.replacen(table, &new_table, 1))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {new_table} (location_id, {columns}) SELECT 1, {columns} FROM {table}"
+    ))
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&format!("DROP TABLE {table}"))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&format!("ALTER TABLE {new_table} RENAME TO {table}"))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Rebuild the single-row `radiator_setting` of older versions (`id = 1`)
+/// into one row per radiator, keeping the stored setting on radiator 1.
+async fn migrate_radiator_setting(pool: &SqlitePool) -> Result<()> {
+    if has_column(pool, "radiator_setting", "radiator_id").await? {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(&RADIATOR_SETTING_TABLE.replacen("radiator_setting", "radiator_setting_new", 1))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO radiator_setting_new (radiator_id, setting, updated_at)
+         SELECT id, setting, updated_at FROM radiator_setting",
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DROP TABLE radiator_setting")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("ALTER TABLE radiator_setting_new RENAME TO radiator_setting")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Add a column to an existing table. `CREATE TABLE IF NOT EXISTS` leaves
+/// databases created by older versions untouched, so new columns go through here.
+async fn add_column_if_missing(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    decl: &str,
+) -> Result<()> {
+    if !has_column(pool, table, column).await? {
+        sqlx::query(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
 }
 
 fn finite_or_none(v: f64) -> Option<f64> {
-    if v.is_finite() { Some(v) } else { None }
+    if v.is_finite() {
+        Some(v)
+    } else {
+        None
+    }
 }
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::db::{self, DegreeDay, WeatherObservation};
+
+/// Indoor base temperature of the Finnish heating degree day method.
+pub const BASE_TEMP_C: f64 = 17.0;
+/// January–June: a day needs heating when its mean is below this.
+pub const SPRING_THRESHOLD_C: f64 = 10.0;
+/// July–December: a day needs heating when its mean is below this.
+pub const AUTUMN_THRESHOLD_C: f64 = 12.0;
+/// Days with fewer hourly observations than this are left out, their mean
+/// would be skewed towards whichever part of the day was covered.
+pub const MIN_HOURS_PER_DAY: i64 = 18;
+
+/// Heating degree days for a single day with the given mean temperature.
+pub fn heating_degree_days(date: NaiveDate, mean_temp_c: f64) -> f64 {
+    let threshold = if date.month() <= 6 {
+        SPRING_THRESHOLD_C
+    } else {
+        AUTUMN_THRESHOLD_C
+    };
+    if mean_temp_c < threshold {
+        BASE_TEMP_C - mean_temp_c
+    } else {
+        0.0
+    }
+}
+
+/// Group hourly observations into local calendar days in `tz` and compute
+/// each day's mean temperature and HDD. Days with too few observations are
+/// skipped.
+pub fn daily(observations: &[WeatherObservation], tz: Tz) -> Vec<DegreeDay> {
+    let mut days: BTreeMap<NaiveDate, (f64, i64)> = BTreeMap::new();
+    for obs in observations {
+        if !obs.temperature_c.is_finite() {
+            continue;
+        }
+        let Ok(ts) = DateTime::parse_from_rfc3339(&obs.timestamp) else {
+            continue;
+        };
+        let entry = days
+            .entry(ts.with_timezone(&tz).date_naive())
+            .or_insert((0.0, 0));
+        entry.0 += obs.temperature_c;
+        entry.1 += 1;
+    }
+
+    days.into_iter()
+        .filter(|(_, (_, hours))| *hours >= MIN_HOURS_PER_DAY)
+        .map(|(date, (sum, hours))| {
+            let mean_temp_c = sum / hours as f64;
+            DegreeDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                mean_temp_c,
+                hours,
+                hdd: heating_degree_days(date, mean_temp_c),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthlyDegreeDays {
+    pub year: i32,
+    pub month: u32,
+    pub hdd: f64,
+    /// Days that had enough observations to be counted.
+    pub days: u32,
+    pub days_in_month: u32,
+}
+
+impl MonthlyDegreeDays {
+    pub fn complete(&self) -> bool {
+        self.days == self.days_in_month
+    }
+}
+
+/// Sum daily aggregates into calendar months, oldest first.
+pub fn monthly(days: &[DegreeDay]) -> Vec<MonthlyDegreeDays> {
+    let mut months: BTreeMap<(i32, u32), (f64, u32)> = BTreeMap::new();
+    for day in days {
+        let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let entry = months.entry((date.year(), date.month())).or_default();
+        entry.0 += day.hdd;
+        entry.1 += 1;
+    }
+
+    months
+        .into_iter()
+        .map(|((year, month), (hdd, days))| MonthlyDegreeDays {
+            year,
+            month,
+            hdd,
+            days,
+            days_in_month: days_in_month(year, month),
+        })
+        .collect()
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let next = first
+        .checked_add_months(chrono::Months::new(1))
+        .unwrap_or(first);
+    (next - first).num_days() as u32
+}
+
+/// Recompute and store the daily aggregates for every local day of
+/// `location` touching `from..to`, plus the day before. Today is left out
+/// until it's over.
+pub async fn update(
+    db: &db::Db,
+    location: &db::Location,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<usize> {
+    let tz = location.tz();
+    let start_of_day = |dt: DateTime<Utc>| {
+        tz.from_local_datetime(
+            &dt.with_timezone(&tz)
+                .date_naive()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        )
+        .earliest()
+        .map(|d| d.to_utc())
+        .unwrap_or(dt)
+    };
+    // Start a day early so a day whose last hours arrived late gets redone.
+    let from = start_of_day(from) - chrono::Duration::days(1);
+    let to = start_of_day(to.min(Utc::now()));
+    if to <= from {
+        return Ok(0);
+    }
+
+    let fmt = |dt: DateTime<Utc>| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let observations = db
+        .get_weather_observations(location.id, &fmt(from), &fmt(to))
+        .await?;
+    let days = daily(&observations, tz);
+    db.upsert_degree_days(location.id, &days).await?;
+    Ok(days.len())
+}
+
+/// The location's whole stored history summed into months.
+pub async fn load_monthly(db: &db::Db, location: &db::Location) -> Result<Vec<MonthlyDegreeDays>> {
+    let days = db.get_degree_days(location.id).await?;
+    Ok(monthly(&days))
+}
@@ -0,0 +1,284 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::BTreeMap;
+
+use super::{format_timestamp, PriceProvider};
+
+pub const DEFAULT_URL: &str = "https://web-api.tp.entsoe.eu/api";
+
+/// Finland's bidding zone.
+pub const DEFAULT_AREA: &str = "10YFI-1--------U";
+
+/// ENTSO-E Transparency Platform day-ahead prices (document type A44).
+#[derive(Debug, Clone)]
+pub struct Entsoe {
+    pub url: String,
+    pub token: String,
+    /// EIC code of the bidding zone.
+    pub area: String,
+    /// Added to positive prices, which ENTSO-E reports without VAT.
+    pub vat_percent: f64,
+}
+
+impl PriceProvider for Entsoe {
+    async fn fetch_prices(&self) -> Result<Vec<(String, f64)>> {
+        let now = Utc::now();
+        let today = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let start = today - Duration::days(1);
+        let end = today + Duration::days(2);
+        let period_start = start.format("%Y%m%d%H%M").to_string();
+        let period_end = end.format("%Y%m%d%H%M").to_string();
+
+        // The token is in the query, so errors are reported without the URL
+        let resp = reqwest::Client::new()
+            .get(&self.url)
+            .query(&[
+                ("securityToken", self.token.as_str()),
+                ("documentType", "A44"),
+                ("in_Domain", self.area.as_str()),
+                ("out_Domain", self.area.as_str()),
+                ("periodStart", period_start.as_str()),
+                ("periodEnd", period_end.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| anyhow!("ENTSO-E request failed: {}", e.without_url()))?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| anyhow!("ENTSO-E response failed: {}", e.without_url()))?;
+        let periods =
+            parse_document(&body).map_err(|e| anyhow!("ENTSO-E returned {status}: {e}"))?;
+        Ok(quarter_hour_prices(&periods, self.vat_percent))
+    }
+}
+
+/// One `Period` of a `TimeSeries`: prices in EUR/MWh by 1-based position.
+#[derive(Debug, Default)]
+struct Period {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    resolution: Option<Duration>,
+    points: Vec<(i64, f64)>,
+}
+
+/// Parse a `Publication_MarketDocument`. An `Acknowledgement_MarketDocument`
+/// (ENTSO-E's error response, e.g. when there is no data) becomes an error
+/// with its reason text.
+fn parse_document(xml: &str) -> Result<Vec<Period>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut periods = Vec::new();
+    let mut period: Option<Period> = None;
+    let mut position: Option<i64> = None;
+    let mut amount: Option<f64> = None;
+    let mut tag = Vec::new();
+    let mut acknowledgement = false;
+    let mut reason = String::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                tag = e.name().local_name().as_ref().to_vec();
+                match tag.as_slice() {
+                    b"Acknowledgement_MarketDocument" => acknowledgement = true,
+                    b"Period" => period = Some(Period::default()),
+                    b"Point" => {
+                        position = None;
+                        amount = None;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::End(e)) => {
+                match e.name().local_name().as_ref() {
+                    b"Point" => {
+                        if let (Some(period), Some(position), Some(amount)) =
+                            (period.as_mut(), position, amount)
+                        {
+                            period.points.push((position, amount));
+                        }
+                    }
+                    b"Period" => periods.extend(period.take()),
+                    _ => {}
+                }
+                tag.clear();
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape()?;
+                if acknowledgement && tag == b"text" {
+                    reason.push_str(&text);
+                }
+                let Some(period) = period.as_mut() else {
+                    buf.clear();
+                    continue;
+                };
+                match tag.as_slice() {
+                    b"start" => period.start = Some(parse_time(&text)?),
+                    b"end" => period.end = Some(parse_time(&text)?),
+                    b"resolution" => period.resolution = Some(parse_resolution(&text)?),
+                    b"position" => position = Some(text.trim().parse()?),
+                    b"price.amount" => amount = Some(text.trim().parse()?),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(anyhow!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if acknowledgement {
+        return Err(anyhow!("{}", reason.trim()));
+    }
+    if periods.is_empty() {
+        return Err(anyhow!("No price periods in ENTSO-E response"));
+    }
+    Ok(periods)
+}
+
+/// ENTSO-E times look like `2026-10-16T22:00Z`.
+fn parse_time(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%MZ")
+        .map(|dt| dt.and_utc())
+        .or_else(|_| DateTime::parse_from_rfc3339(s).map(|dt| dt.to_utc()))
+        .map_err(|_| anyhow!("Invalid ENTSO-E time `{s}`"))
+}
+
+/// ISO 8601 durations as used for `resolution`, e.g. `PT15M` or `PT60M`.
+fn parse_resolution(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let invalid = || anyhow!("Unsupported ENTSO-E resolution `{s}`");
+    let rest = s.strip_prefix("PT").ok_or_else(invalid)?;
+    if let Some(minutes) = rest.strip_suffix('M') {
+        return Ok(Duration::minutes(minutes.parse().map_err(|_| invalid())?));
+    }
+    if let Some(hours) = rest.strip_suffix('H') {
+        return Ok(Duration::hours(hours.parse().map_err(|_| invalid())?));
+    }
+    Err(invalid())
+}
+
+/// Spread the periods over 15-minute slots in c/kWh with VAT, the shape
+/// porssisahko prices come in. Positions left out of a period repeat the
+/// previous price (curve type A03). Finer resolutions win where time series
+/// overlap.
+fn quarter_hour_prices(periods: &[Period], vat_percent: f64) -> Vec<(String, f64)> {
+    let quarter = Duration::minutes(15);
+    let mut ordered: Vec<&Period> = periods.iter().collect();
+    ordered.sort_by_key(|p| std::cmp::Reverse(p.resolution));
+
+    let mut slots: BTreeMap<DateTime<Utc>, f64> = BTreeMap::new();
+    for period in ordered {
+        let (Some(start), Some(end), Some(resolution)) =
+            (period.start, period.end, period.resolution)
+        else {
+            continue;
+        };
+        if resolution < quarter {
+            continue;
+        }
+        let mut points = period.points.clone();
+        points.sort_by_key(|(position, _)| *position);
+        let count = (end - start).num_seconds() / resolution.num_seconds();
+        for position in 1..=count {
+            let Some(&(_, eur_mwh)) = points.iter().rev().find(|(p, _)| *p <= position) else {
+                continue;
+            };
+            // EUR/MWh → c/kWh, with VAT only on positive prices
+            let mut cents = eur_mwh / 10.0;
+            if cents > 0.0 {
+                cents *= 1.0 + vat_percent / 100.0;
+            }
+            let slot_start = start + resolution * (position - 1) as i32;
+            let mut slot = slot_start;
+            while slot < slot_start + resolution {
+                slots.insert(slot, cents);
+                slot += quarter;
+            }
+        }
+    }
+
+    slots
+        .into_iter()
+        .map(|(ts, cents)| (format_timestamp(ts), cents))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A44: &str = include_str!("testdata/entsoe_a44.xml");
+    const ACKNOWLEDGEMENT: &str = include_str!("testdata/entsoe_acknowledgement.xml");
+
+    fn prices(vat_percent: f64) -> BTreeMap<String, f64> {
+        let periods = parse_document(A44).unwrap();
+        quarter_hour_prices(&periods, vat_percent)
+            .into_iter()
+            .collect()
+    }
+
+    fn assert_price(prices: &BTreeMap<String, f64>, timestamp: &str, expected: f64) {
+        let price = prices[timestamp];
+        assert!(
+            (price - expected).abs() < 1e-9,
+            "{timestamp}: {price} != {expected}"
+        );
+    }
+
+    #[test]
+    fn parses_both_time_series() {
+        let periods = parse_document(A44).unwrap();
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].resolution, Some(Duration::minutes(60)));
+        assert_eq!(periods[1].resolution, Some(Duration::minutes(15)));
+        assert_eq!(periods[0].points, [(1, 50.0), (2, -5.0), (4, 100.0)]);
+    }
+
+    #[test]
+    fn covers_every_quarter_hour() {
+        let prices = prices(0.0);
+        assert_eq!(prices.len(), 16);
+        assert!(prices.contains_key("2026-10-16T22:00:00Z"));
+        assert!(prices.contains_key("2026-10-17T01:45:00Z"));
+    }
+
+    #[test]
+    fn quarter_hours_win_over_hours_where_they_overlap() {
+        let prices = prices(0.0);
+        assert_price(&prices, "2026-10-17T00:00:00Z", 1.0);
+        assert_price(&prices, "2026-10-17T00:30:00Z", 2.0);
+    }
+
+    #[test]
+    fn left_out_positions_repeat_the_previous_price() {
+        let prices = prices(0.0);
+        assert_price(&prices, "2026-10-17T00:15:00Z", 1.0);
+        assert_price(&prices, "2026-10-17T00:45:00Z", 2.0);
+    }
+
+    #[test]
+    fn vat_is_added_to_positive_prices_only() {
+        let prices = prices(25.5);
+        assert_price(&prices, "2026-10-16T22:00:00Z", 5.0 * 1.255);
+        assert_price(&prices, "2026-10-16T23:00:00Z", -0.5);
+        assert_price(&prices, "2026-10-17T01:00:00Z", 10.0 * 1.255);
+    }
+
+    #[test]
+    fn acknowledgement_is_an_error_with_its_reason() {
+        let error = parse_document(ACKNOWLEDGEMENT).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No matching data found for Data item Day-ahead Prices"
+        );
+    }
+}
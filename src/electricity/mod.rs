@@ -0,0 +1,95 @@
+use anyhow::Result;
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::config::{Config, ElectricityProviderKind};
+
+pub mod entsoe;
+pub mod porssisahko;
+
+/// Local hours (inclusive start, exclusive end) the night transfer tariff
+/// applies, wrapping past midnight.
+const NIGHT_TRANSFER_HOURS: (u32, u32) = (22, 7);
+
+/// What's paid on top of the spot price, in c/kWh before VAT. The spot
+/// prices from porssisahko already include VAT.
+#[derive(Clone, Debug, Default)]
+pub struct PriceComponents {
+    pub margin_cents: f64,
+    pub transfer_day_cents: f64,
+    pub transfer_night_cents: f64,
+    pub tax_cents: f64,
+    pub vat_percent: f64,
+}
+
+impl PriceComponents {
+    /// Whether anything is added to the spot price. Prices are shown as
+    /// plain spot prices otherwise.
+    pub fn is_configured(&self) -> bool {
+        self.margin_cents != 0.0
+            || self.transfer_day_cents != 0.0
+            || self.transfer_night_cents != 0.0
+            || self.tax_cents != 0.0
+    }
+
+    pub fn transfer_cents(&self, at: DateTime<Utc>, tz: Tz) -> f64 {
+        let hour = at.with_timezone(&tz).hour();
+        let (night_start, night_end) = NIGHT_TRANSFER_HOURS;
+        if hour >= night_start || hour < night_end {
+            self.transfer_night_cents
+        } else {
+            self.transfer_day_cents
+        }
+    }
+
+    /// The price of a kWh used at `at` with every component and VAT.
+    pub fn total(&self, spot_cents: f64, at: DateTime<Utc>, tz: Tz) -> f64 {
+        let extras = self.margin_cents + self.transfer_cents(at, tz) + self.tax_cents;
+        spot_cents + extras * (1.0 + self.vat_percent / 100.0)
+    }
+}
+
+/// A source of day-ahead spot prices. Prices are returned as
+/// `(timestamp, c/kWh incl. VAT)` pairs, one per 15-minute slot, with
+/// timestamps formatted like the rest of the database.
+pub trait PriceProvider {
+    async fn fetch_prices(&self) -> Result<Vec<(String, f64)>>;
+}
+
+/// The provider selected in `Config`.
+#[derive(Debug, Clone)]
+pub enum Provider {
+    Porssisahko(porssisahko::Porssisahko),
+    Entsoe(entsoe::Entsoe),
+}
+
+impl Provider {
+    pub fn from_config(config: &Config) -> Self {
+        match config.electricity_provider {
+            ElectricityProviderKind::Porssisahko => {
+                Provider::Porssisahko(porssisahko::Porssisahko {
+                    url: config.porssisahko_url.clone(),
+                })
+            }
+            ElectricityProviderKind::Entsoe => Provider::Entsoe(entsoe::Entsoe {
+                url: config.entsoe_url.clone(),
+                token: config.entsoe_token.clone().unwrap_or_default(),
+                area: config.entsoe_area.clone(),
+                vat_percent: config.price_components.vat_percent,
+            }),
+        }
+    }
+}
+
+impl PriceProvider for Provider {
+    async fn fetch_prices(&self) -> Result<Vec<(String, f64)>> {
+        match self {
+            Provider::Porssisahko(p) => p.fetch_prices().await,
+            Provider::Entsoe(p) => p.fetch_prices().await,
+        }
+    }
+}
+
+fn format_timestamp(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+use super::{format_timestamp, PriceProvider};
+
+pub const DEFAULT_URL: &str = "https://api.porssisahko.net/v2/latest-prices.json";
+
+/// porssisahko.net's latest prices, already in c/kWh with Finnish VAT.
+#[derive(Debug, Clone)]
+pub struct Porssisahko {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PricesResponse {
+    pub prices: Vec<PriceEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceEntry {
+    pub price: f64,
+    pub start_date: String,
+}
+
+impl PriceProvider for Porssisahko {
+    async fn fetch_prices(&self) -> Result<Vec<(String, f64)>> {
+        let resp: PricesResponse = reqwest::get(&self.url).await?.json().await?;
+
+        let prices: Vec<(String, f64)> = resp
+            .prices
+            .iter()
+            .filter_map(|entry| {
+                let ts = normalize_timestamp(&entry.start_date)?;
+                Some((ts, entry.price))
+            })
+            .collect();
+
+        Ok(prices)
+    }
+}
+
+fn normalize_timestamp(timestamp: &str) -> Option<String> {
+    let dt = chrono::DateTime::parse_from_rfc3339(timestamp).ok()?;
+    Some(format_timestamp(dt.to_utc()))
+}
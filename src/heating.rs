@@ -0,0 +1,278 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::weather::ForecastPoint;
+
+/// Local hours (inclusive start, exclusive end) when a clear sky is counted
+/// as solar gain through the windows.
+const DAYLIGHT_HOURS: std::ops::Range<u32> = 9..17;
+
+/// One breakpoint of a heating curve: below `below_c` the radiator goes to
+/// `setting`, unless a colder breakpoint matches first.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurveStep {
+    pub below_c: f64,
+    pub setting: f64,
+}
+
+/// Maps an outdoor temperature to a radiator dial setting. Steps are kept
+/// sorted coldest first; `otherwise` applies above the warmest breakpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatingCurve {
+    pub steps: Vec<CurveStep>,
+    pub otherwise: f64,
+}
+
+impl Default for HeatingCurve {
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                CurveStep {
+                    below_c: -1.0,
+                    setting: 3.5,
+                },
+                CurveStep {
+                    below_c: 10.0,
+                    setting: 2.0,
+                },
+            ],
+            otherwise: 0.0,
+        }
+    }
+}
+
+impl HeatingCurve {
+    /// Build a curve, sorting the steps and rejecting duplicate breakpoints
+    /// or settings that aren't a non-negative number.
+    pub fn new(mut steps: Vec<CurveStep>, otherwise: f64) -> Result<Self> {
+        for setting in steps.iter().map(|s| s.setting).chain([otherwise]) {
+            if !setting.is_finite() || setting < 0.0 {
+                return Err(anyhow!("Invalid radiator setting {setting}"));
+            }
+        }
+        if steps.iter().any(|s| !s.below_c.is_finite()) {
+            return Err(anyhow!("Breakpoint temperatures must be numbers"));
+        }
+        steps.sort_by(|a, b| a.below_c.total_cmp(&b.below_c));
+        if let Some(w) = steps.windows(2).find(|w| w[0].below_c == w[1].below_c) {
+            return Err(anyhow!("Duplicate breakpoint {}°C", w[0].below_c));
+        }
+        Ok(Self { steps, otherwise })
+    }
+
+    /// The setting for an outdoor temperature, NaN if the temperature is unknown.
+    pub fn setting_for(&self, temp_c: f64) -> f64 {
+        if !temp_c.is_finite() {
+            return f64::NAN;
+        }
+        self.steps
+            .iter()
+            .find(|s| temp_c < s.below_c)
+            .map(|s| s.setting)
+            .unwrap_or(self.otherwise)
+    }
+
+    /// Every distinct dial position the curve uses, lowest first.
+    pub fn settings(&self) -> Vec<f64> {
+        let mut settings: Vec<f64> = self
+            .steps
+            .iter()
+            .map(|s| s.setting)
+            .chain([self.otherwise])
+            .collect();
+        settings.sort_by(f64::total_cmp);
+        settings.dedup();
+        settings
+    }
+
+    /// Whether `setting` is what the curve gives anywhere within
+    /// `deadband_c` degrees of `temp_c`.
+    pub fn within_deadband(&self, temp_c: f64, setting: f64, deadband_c: f64) -> bool {
+        let (low, high) = (temp_c - deadband_c, temp_c + deadband_c);
+        let mut region_start = f64::NEG_INFINITY;
+        for step in &self.steps {
+            if step.setting == setting && region_start <= high && low < step.below_c {
+                return true;
+            }
+            region_start = step.below_c;
+        }
+        self.otherwise == setting && region_start <= high
+    }
+
+    /// The curve's dial position closest to `value`.
+    pub fn snap(&self, value: f64) -> f64 {
+        self.settings()
+            .into_iter()
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+            .unwrap_or(value)
+    }
+}
+
+/// What changed the radiator setting, recorded in its history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    /// The buttons on the location page.
+    Web,
+    /// The action button on a radiator push notification.
+    Notification,
+    /// Anything posting to `/radiator` on the user's behalf, e.g. a home
+    /// automation script.
+    Automation,
+    /// A command on the radiator's MQTT topic, e.g. from Home Assistant or a
+    /// smart thermostatic valve.
+    Mqtt,
+}
+
+impl SettingSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SettingSource::Web => "web",
+            SettingSource::Notification => "notification",
+            SettingSource::Automation => "automation",
+            SettingSource::Mqtt => "mqtt",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "web" => Some(SettingSource::Web),
+            "notification" => Some(SettingSource::Notification),
+            "automation" => Some(SettingSource::Automation),
+            "mqtt" => Some(SettingSource::Mqtt),
+            _ => None,
+        }
+    }
+}
+
+/// How a dial setting reads in notifications and buttons.
+pub fn setting_label(setting: f64) -> String {
+    if setting == 0.0 {
+        "off".to_string()
+    } else {
+        setting.to_string()
+    }
+}
+
+/// The setting currently on the dial and since when.
+#[derive(Debug, Clone, Copy)]
+pub struct CurrentSetting {
+    pub setting: f64,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// Nothing stored yet, or the forecast calls for the setting on the dial.
+    Curve,
+    /// The curve wants a change, but the temperature is within the deadband
+    /// of the current setting's range.
+    Deadband,
+    /// The curve wants a change, but the current setting hasn't been held
+    /// for the minimum dwell time.
+    Dwell,
+    /// The curve wants a change and neither guard applies.
+    Change,
+}
+
+impl Reason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Reason::Curve => "curve",
+            Reason::Deadband => "within deadband",
+            Reason::Dwell => "minimum dwell not reached",
+            Reason::Change => "change",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Recommendation {
+    /// What to set the dial to. NaN when the temperature is unknown.
+    pub setting: f64,
+    /// What the curve alone says, before deadband and dwell.
+    pub curve_setting: f64,
+    pub reason: Reason,
+}
+
+/// Recommend a dial setting for `temp_c`, holding the current setting while
+/// the temperature stays within `deadband_c` of its range or until it has
+/// been on the dial for `min_dwell`.
+pub fn recommend(
+    curve: &HeatingCurve,
+    temp_c: f64,
+    current: Option<CurrentSetting>,
+    deadband_c: f64,
+    min_dwell: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Recommendation {
+    let curve_setting = curve.setting_for(temp_c);
+    let hold = |reason| Recommendation {
+        setting: current.map(|c| c.setting).unwrap_or(curve_setting),
+        curve_setting,
+        reason,
+    };
+
+    let Some(current) = current.filter(|_| curve_setting.is_finite()) else {
+        return Recommendation {
+            setting: curve_setting,
+            curve_setting,
+            reason: Reason::Curve,
+        };
+    };
+    if (current.setting - curve_setting).abs() < 0.01 {
+        return hold(Reason::Curve);
+    }
+    if curve.within_deadband(temp_c, current.setting, deadband_c) {
+        return hold(Reason::Deadband);
+    }
+    if now - current.since < min_dwell {
+        return hold(Reason::Dwell);
+    }
+    Recommendation {
+        setting: curve_setting,
+        curve_setting,
+        reason: Reason::Change,
+    }
+}
+
+/// How much wind and sunshine shift the temperature the house "feels".
+#[derive(Debug, Clone, Copy)]
+pub struct TemperatureAdjustment {
+    /// Degrees subtracted per m/s of wind.
+    pub wind_c_per_ms: f64,
+    /// Degrees added for a cloudless daytime hour, scaled down by cloud cover.
+    pub solar_gain_c: f64,
+}
+
+impl TemperatureAdjustment {
+    /// Effective temperature of a forecast hour. Missing wind or cloud data
+    /// leaves that part of the adjustment out.
+    pub fn effective_temperature(&self, point: &ForecastPoint, tz: Tz) -> f64 {
+        let mut temp = point.temperature_c;
+        if point.wind_speed_ms.is_finite() {
+            temp -= self.wind_c_per_ms * point.wind_speed_ms;
+        }
+        let local_hour = point.timestamp.with_timezone(&tz).hour();
+        if point.cloud_cover.is_finite() && DAYLIGHT_HOURS.contains(&local_hour) {
+            let clear = 1.0 - (point.cloud_cover / 100.0).clamp(0.0, 1.0);
+            temp += self.solar_gain_c * clear;
+        }
+        temp
+    }
+
+    /// `ForecastPoint::weighted_avg_temperature` over effective temperatures.
+    pub fn weighted_avg(
+        &self,
+        points: &[ForecastPoint],
+        tz: Tz,
+        decay: f64,
+        horizon_hours: usize,
+        skip_hours: usize,
+    ) -> f64 {
+        ForecastPoint::weighted_avg_by(points, decay, horizon_hours, skip_hours, |p| {
+            self.effective_temperature(p, tz)
+        })
+    }
+}
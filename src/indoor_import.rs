@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use tracing::info;
+
+use crate::db::{self, SensorReading};
+
+/// Local timestamp layouts thermometer apps tend to export.
+const LOCAL_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%d.%m.%Y %H:%M:%S",
+    "%d.%m.%Y %H:%M",
+];
+
+fn parse_timestamp(value: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.to_utc());
+    }
+    if let Ok(epoch) = value.parse::<i64>() {
+        return DateTime::from_timestamp(epoch, 0);
+    }
+    LOCAL_FORMATS.iter().find_map(|f| {
+        let naive = NaiveDateTime::parse_from_str(value, f).ok()?;
+        Some(tz.from_local_datetime(&naive).earliest()?.to_utc())
+    })
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+}
+
+/// Parse `timestamp,temperature[,humidity]` rows. Comma, semicolon and tab
+/// separated files all work; a header and any other unparseable lines are
+/// skipped. Timestamps without an offset are local time in `tz`.
+pub fn parse_csv(content: &str, tz: Tz) -> (Vec<SensorReading>, usize) {
+    let mut readings = Vec::new();
+    let mut skipped = 0;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let delimiter = [';', '\t', ',']
+            .into_iter()
+            .find(|d| line.contains(*d))
+            .unwrap_or(',');
+        let fields: Vec<&str> = line
+            .split(delimiter)
+            .map(|f| f.trim().trim_matches('"'))
+            .collect();
+        let parsed = (|| {
+            let timestamp = parse_timestamp(fields.first()?, tz)?;
+            let temperature_c = parse_number(fields.get(1)?)?;
+            Some(SensorReading {
+                timestamp: timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                temperature_c,
+                humidity: fields.get(2).and_then(|h| parse_number(h)),
+            })
+        })();
+        match parsed {
+            Some(r) => readings.push(r),
+            None => skipped += 1,
+        }
+    }
+    (readings, skipped)
+}
+
+/// `weather import-indoor --sensor <id> [--location <slug>] <file.csv>`
+///
+/// Without `--location` a new sensor goes to the first location and a known
+/// one stays where it is.
+pub async fn run_cli(db: &db::Db, args: &[String]) -> Result<()> {
+    let mut sensor = None;
+    let mut slug = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sensor" => sensor = Some(args.next().context("--sensor needs an id")?.clone()),
+            "--location" => slug = Some(args.next().context("--location needs a slug")?.clone()),
+            other if other.starts_with("--") => {
+                return Err(anyhow!("Unknown import-indoor argument {other}"))
+            }
+            other => path = Some(other.to_string()),
+        }
+    }
+    let sensor = sensor.context("--sensor is required")?;
+    let path = path.context("Give the CSV file to import")?;
+
+    let location = match &slug {
+        Some(slug) => db
+            .get_location(slug)
+            .await?
+            .ok_or_else(|| anyhow!("Unknown location {slug}"))?,
+        None => match db.get_sensor_location_id(&sensor).await? {
+            Some(id) => db
+                .list_locations()
+                .await?
+                .into_iter()
+                .find(|l| l.id == id)
+                .context("Sensor's location no longer exists")?,
+            None => db.default_location().await?,
+        },
+    };
+    db.upsert_sensor(&sensor, location.id).await?;
+
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Reading {path}"))?;
+    let (readings, skipped) = parse_csv(&content, location.tz());
+    db.insert_sensor_readings(&sensor, &readings).await?;
+    info!(
+        "Imported {} readings for sensor {sensor} at {} ({skipped} lines skipped)",
+        readings.len(),
+        location.slug
+    );
+    Ok(())
+}
@@ -7,10 +7,21 @@ use tower_http::services::ServeDir;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+mod accuracy;
+mod appliances;
+mod backfill;
+mod cheapest_window;
 mod config;
+mod curve_tuning;
 mod db;
+mod degree_days;
 mod electricity;
+mod heating;
+mod indoor_import;
+mod mqtt;
 mod notify;
+mod preheat;
+mod price_alerts;
 mod routes;
 mod scheduler;
 mod weather;
@@ -33,6 +44,14 @@ async fn main() -> Result<()> {
     let config = config::Config::from_env()?;
 
     let db = db::Db::init_db(&config.db_path).await?;
+    db.seed_location(&config.seed_location()).await?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("backfill") => return backfill::run_cli(&db, &config, &args[1..]).await,
+        Some("import-indoor") => return indoor_import::run_cli(&db, &args[1..]).await,
+        _ => {}
+    }
     info!("Database initialized at {}", config.db_path);
 
     let state = AppState {
@@ -44,8 +63,54 @@ async fn main() -> Result<()> {
     info!("Background scheduler started");
 
     let app = Router::new()
-        .route("/", get(routes::index::handler))
+        .route("/", get(routes::locations::handler))
+        .route("/locations", post(routes::locations::add))
+        .route("/l/{slug}", get(routes::index::handler))
+        .route("/l/{slug}/degree-days", get(routes::degree_days::handler))
         .route("/radiator", post(routes::index::radiator_handler))
+        .route("/settings", get(routes::settings::handler))
+        .route(
+            "/settings/heating-curve",
+            post(routes::settings::save_heating_curve),
+        )
+        .route("/settings/radiators", post(routes::settings::add_radiator))
+        .route(
+            "/settings/radiators/{id}",
+            post(routes::settings::update_radiator),
+        )
+        .route(
+            "/settings/appliances",
+            post(routes::settings::add_appliance),
+        )
+        .route(
+            "/settings/appliances/{id}",
+            post(routes::settings::update_appliance),
+        )
+        .route(
+            "/settings/appliances/{id}/delete",
+            post(routes::settings::delete_appliance),
+        )
+        .route(
+            "/settings/heating-curve/propose",
+            post(routes::settings::propose_heating_curve),
+        )
+        .route(
+            "/settings/heating-curve/proposals/{id}/approve",
+            post(routes::settings::approve_proposal),
+        )
+        .route(
+            "/settings/heating-curve/proposals/{id}/reject",
+            post(routes::settings::reject_proposal),
+        )
+        .route(
+            "/api/sensors/{id}/readings",
+            post(routes::sensors::post_readings),
+        )
+        .route("/api/radiator-history", get(routes::radiator::history))
+        .route("/accuracy", get(routes::accuracy::handler))
+        .route("/api/accuracy", get(routes::accuracy::json))
+        .route("/cheapest-window", get(routes::cheapest_window::handler))
+        .route("/api/cheapest-window", get(routes::cheapest_window::json))
         .route("/push/subscribe", post(routes::push::subscribe))
         .route("/push/unsubscribe", post(routes::push::unsubscribe))
         .route("/push/test-summary", post(routes::push::test_summary))
@@ -82,7 +147,7 @@ async fn serve_manifest() -> impl axum::response::IntoResponse {
     )
 }
 
-const CSS: &'static str = "/assets/styles.css";
+const CSS: &str = "/assets/styles.css";
 
 fn css() -> Router {
     // Serve embedded css in release
@@ -108,7 +173,7 @@ fn css() -> Router {
     }
 }
 
-const JS: &'static str = "/assets/script.js";
+const JS: &str = "/assets/script.js";
 
 fn js(vapid_public_key: String) -> Router {
     // Serve embedded script in release
@@ -0,0 +1,305 @@
+use anyhow::Result;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde_json::json;
+use tracing::{error, info};
+
+use crate::{
+    config::MqttConfig,
+    db::{self, Radiator},
+    heating::SettingSource,
+};
+
+/// Values published after each scheduler run. Unknown values are left out
+/// so Home Assistant keeps the last known state.
+pub struct State {
+    pub temperature_c: f64,
+    pub min_temp_c: f64,
+    pub max_temp_c: f64,
+    pub price_cents_kwh: Option<f64>,
+    /// Highest dial position on the heating curve, the upper bound of the
+    /// radiator setting entities.
+    pub max_setting: f64,
+    pub radiators: Vec<RadiatorStatus>,
+}
+
+pub struct RadiatorStatus {
+    pub radiator: Radiator,
+    pub recommended: f64,
+    pub current: Option<f64>,
+}
+
+#[derive(Clone)]
+pub struct Mqtt {
+    client: AsyncClient,
+    config: MqttConfig,
+}
+
+struct Sensor {
+    object_id: String,
+    name: String,
+    state_topic: String,
+    unit: Option<&'static str>,
+    device_class: Option<&'static str>,
+    icon: Option<&'static str>,
+}
+
+/// Connect to the broker in the background. Publishes are queued while the
+/// connection is down and the client keeps reconnecting. Radiator settings
+/// sent to `{prefix}/radiator/{slug}/set` are stored as they arrive.
+pub fn spawn(db: db::Db, config: MqttConfig) -> Mqtt {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(std::time::Duration::from_secs(30));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    options.set_last_will(LastWill::new(
+        availability_topic(&config),
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+    let (client, mut eventloop) = AsyncClient::new(options, 64);
+    let mqtt = Mqtt { client, config };
+
+    // Commands are handled in order off the event loop, which has to keep
+    // polling for the publishes the handler makes.
+    let (commands_tx, mut commands_rx) = tokio::sync::mpsc::unbounded_channel::<rumqttc::Publish>();
+    let handler = mqtt.clone();
+    tokio::spawn(async move {
+        while let Some(publish) = commands_rx.recv().await {
+            let payload = String::from_utf8_lossy(&publish.payload);
+            if let Err(e) = handler.handle_command(&db, &publish.topic, &payload).await {
+                error!("Failed to handle MQTT message on {}: {e}", publish.topic);
+            }
+        }
+    });
+
+    let connection = mqtt.clone();
+    tokio::spawn(async move {
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!(
+                        "Connected to MQTT broker {}:{}",
+                        connection.config.host, connection.config.port
+                    );
+                    connection.publish("status", "online");
+                    let commands = connection.topic("radiator/+/set");
+                    if let Err(e) = connection.client.try_subscribe(&commands, QoS::AtLeastOnce) {
+                        error!("Failed to subscribe to {commands}: {e}");
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let _ = commands_tx.send(publish);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("MQTT connection error: {e}");
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                }
+            }
+        }
+    });
+
+    mqtt
+}
+
+fn availability_topic(config: &MqttConfig) -> String {
+    format!("{}/status", config.topic_prefix)
+}
+
+/// A setting as sent by Home Assistant or a TRV: a dial position, or `off`.
+fn parse_setting(payload: &str) -> Option<f64> {
+    let payload = payload.trim();
+    if payload.eq_ignore_ascii_case("off") {
+        return Some(0.0);
+    }
+    payload.parse().ok().filter(|v: &f64| v.is_finite())
+}
+
+impl Mqtt {
+    fn topic(&self, suffix: &str) -> String {
+        format!("{}/{suffix}", self.config.topic_prefix)
+    }
+
+    fn availability_topic(&self) -> String {
+        availability_topic(&self.config)
+    }
+
+    /// Queue a retained message under the topic prefix. Never waits on the
+    /// broker, so an unreachable broker can't stall the scheduler.
+    fn publish(&self, suffix: &str, payload: impl Into<Vec<u8>>) {
+        let topic = self.topic(suffix);
+        if let Err(e) = self
+            .client
+            .try_publish(&topic, QoS::AtLeastOnce, true, payload)
+        {
+            error!("Failed to publish MQTT message to {topic}: {e}");
+        }
+    }
+
+    fn publish_value(&self, suffix: &str, value: f64) {
+        if value.is_finite() {
+            self.publish(suffix, format!("{value:.1}"));
+        }
+    }
+
+    /// Store a setting received on `{prefix}/radiator/{slug}/set`, snapped
+    /// to the curve like the buttons on the web page, and echo it back on
+    /// the radiator's state topic.
+    async fn handle_command(&self, db: &db::Db, topic: &str, payload: &str) -> Result<()> {
+        let Some(slug) = topic
+            .strip_prefix(&self.topic("radiator/"))
+            .and_then(|rest| rest.strip_suffix("/set"))
+        else {
+            return Ok(());
+        };
+        let Some(value) = parse_setting(payload) else {
+            anyhow::bail!("`{payload}` is not a radiator setting");
+        };
+        let Some(radiator) = db
+            .list_radiators()
+            .await?
+            .into_iter()
+            .find(|r| r.slug == slug)
+        else {
+            anyhow::bail!("no radiator `{slug}`");
+        };
+        let setting = db.get_heating_curve().await?.snap(value);
+        info!("MQTT set {} to {setting:.1} (sent {value})", radiator.slug);
+        db.set_radiator_setting(radiator.id, setting, SettingSource::Mqtt)
+            .await?;
+        self.publish_value(&format!("radiator/{slug}/setting"), setting);
+        Ok(())
+    }
+
+    fn sensors(&self, state: &State) -> Vec<Sensor> {
+        let temperature = |object_id: &str, name: &str, suffix: &str| Sensor {
+            object_id: object_id.to_string(),
+            name: name.to_string(),
+            state_topic: self.topic(suffix),
+            unit: Some("°C"),
+            device_class: Some("temperature"),
+            icon: None,
+        };
+        let mut sensors = vec![
+            temperature("temperature", "Temperature", "temperature"),
+            temperature("forecast_min", "Forecast min 24h", "forecast/min"),
+            temperature("forecast_max", "Forecast max 24h", "forecast/max"),
+            Sensor {
+                object_id: "electricity_price".to_string(),
+                name: "Electricity price".to_string(),
+                state_topic: self.topic("electricity/price"),
+                unit: Some("c/kWh"),
+                device_class: None,
+                icon: Some("mdi:flash"),
+            },
+        ];
+        for status in &state.radiators {
+            let radiator = &status.radiator;
+            sensors.push(Sensor {
+                object_id: format!("radiator_{}_recommended", radiator.slug),
+                name: format!("{} recommended setting", radiator.name),
+                state_topic: self.topic(&format!("radiator/{}/recommended", radiator.slug)),
+                unit: None,
+                device_class: None,
+                icon: Some("mdi:radiator"),
+            });
+        }
+        sensors
+    }
+
+    /// Announce every published value to Home Assistant via MQTT discovery,
+    /// plus a number entity per radiator that sets its stored setting.
+    fn publish_discovery(&self, state: &State) -> Result<()> {
+        let node_id: String = self
+            .config
+            .client_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let device = json!({
+            "identifiers": [node_id],
+            "name": "Weather",
+        });
+
+        let mut entities = Vec::new();
+        for sensor in self.sensors(state) {
+            let mut payload = json!({
+                "name": sensor.name,
+                "unique_id": format!("{node_id}_{}", sensor.object_id),
+                "state_topic": sensor.state_topic,
+                "state_class": "measurement",
+                "availability_topic": self.availability_topic(),
+                "device": device,
+            });
+            if let Some(unit) = sensor.unit {
+                payload["unit_of_measurement"] = json!(unit);
+            }
+            if let Some(device_class) = sensor.device_class {
+                payload["device_class"] = json!(device_class);
+            }
+            if let Some(icon) = sensor.icon {
+                payload["icon"] = json!(icon);
+            }
+            entities.push(("sensor", sensor.object_id, payload));
+        }
+        for status in &state.radiators {
+            let radiator = &status.radiator;
+            let object_id = format!("radiator_{}_setting", radiator.slug);
+            let payload = json!({
+                "name": format!("{} setting", radiator.name),
+                "unique_id": format!("{node_id}_{object_id}"),
+                "state_topic": self.topic(&format!("radiator/{}/setting", radiator.slug)),
+                "command_topic": self.topic(&format!("radiator/{}/set", radiator.slug)),
+                "min": 0,
+                "max": state.max_setting,
+                "step": 0.5,
+                "icon": "mdi:radiator",
+                "availability_topic": self.availability_topic(),
+                "device": device,
+            });
+            entities.push(("number", object_id, payload));
+        }
+
+        for (component, object_id, payload) in entities {
+            let topic = format!(
+                "{}/{component}/{node_id}/{object_id}/config",
+                self.config.discovery_prefix
+            );
+            self.client.try_publish(
+                topic,
+                QoS::AtLeastOnce,
+                true,
+                serde_json::to_vec(&payload)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Publish discovery messages and the current state. Discovery is resent
+    /// every time so radiators added or renamed in Settings show up.
+    pub fn publish_state(&self, state: &State) -> Result<()> {
+        self.publish_discovery(state)?;
+        self.publish_value("temperature", state.temperature_c);
+        self.publish_value("forecast/min", state.min_temp_c);
+        self.publish_value("forecast/max", state.max_temp_c);
+        if let Some(price) = state.price_cents_kwh {
+            self.publish_value("electricity/price", price);
+        }
+        for status in &state.radiators {
+            let slug = &status.radiator.slug;
+            self.publish_value(&format!("radiator/{slug}/recommended"), status.recommended);
+            if let Some(current) = status.current {
+                self.publish_value(&format!("radiator/{slug}/setting"), current);
+            }
+        }
+        Ok(())
+    }
+}
@@ -14,7 +14,7 @@ use p256::{
 use rand::rngs::OsRng;
 use sha2::Sha256;
 
-use crate::db::Subscription;
+use crate::{config::Config, db::Subscription};
 
 pub struct VapidConfig {
     pub subject: String,
@@ -22,6 +22,16 @@ pub struct VapidConfig {
     pub private_key_b64: String,
 }
 
+impl VapidConfig {
+    pub fn from_config(config: &Config) -> Self {
+        VapidConfig {
+            subject: config.vapid_subject.clone(),
+            public_key_b64: config.vapid_public_key.clone(),
+            private_key_b64: config.vapid_private_key.clone(),
+        }
+    }
+}
+
 fn make_client() -> reqwest::Client {
     reqwest::Client::builder()
         .use_rustls_tls()
@@ -43,6 +53,22 @@ pub async fn send_all(
     results
 }
 
+/// `send_all`, logging how many subscribers `what` reached.
+pub async fn send_all_logged(
+    subscriptions: &[Subscription],
+    message: &str,
+    vapid: &VapidConfig,
+    what: &str,
+) {
+    let results = send_all(subscriptions, message, vapid).await;
+    let success_count = results.iter().filter(|r| r.is_ok()).count();
+    tracing::info!(
+        "{what} sent to {}/{} subscribers",
+        success_count,
+        subscriptions.len()
+    );
+}
+
 pub async fn send_one_sub(sub: &Subscription, message: &str, vapid: &VapidConfig) -> Result<()> {
     send_one(&make_client(), sub, message, vapid).await
 }
@@ -108,7 +134,7 @@ fn encrypt_payload(plaintext: &[u8], ua_pubkey: &PublicKey, auth_secret: &[u8])
     info.extend_from_slice(&ua_pubkey_bytes);
     info.extend_from_slice(&server_pubkey_bytes);
 
-    let hk = Hkdf::<Sha256>::new(Some(auth_secret), shared_bytes.as_slice());
+    let hk = Hkdf::<Sha256>::new(Some(auth_secret), &shared_bytes[..]);
     let mut ikm = [0u8; 32];
     hk.expand(&info, &mut ikm)
         .map_err(|_| anyhow!("HKDF expand failed for IKM"))?;
@@ -133,9 +159,9 @@ fn encrypt_payload(plaintext: &[u8], ua_pubkey: &PublicKey, auth_secret: &[u8])
     msg.push(0x02);
 
     let cipher = Aes128Gcm::new_from_slice(&cek)?;
-    let nonce = Nonce::from_slice(&nonce_bytes);
+    let nonce = Nonce::from(nonce_bytes);
     let ciphertext = cipher
-        .encrypt(nonce, msg.as_slice())
+        .encrypt(&nonce, msg.as_slice())
         .map_err(|_| anyhow!("AES-GCM encryption failed"))?;
 
     // Build aes128gcm payload:
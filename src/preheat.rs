@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+use crate::{
+    config::Config, db, electricity::PriceComponents, heating::HeatingCurve, weather::ForecastPoint,
+};
+
+/// Hours planned ahead. Day-ahead prices rarely reach further.
+const HORIZON_HOURS: usize = 36;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shift {
+    /// Cheap hour, heat is banked by going one dial position up.
+    Raise,
+    /// Expensive hour, the banked heat lets the dial go one position down.
+    Lower,
+    Hold,
+}
+
+impl Shift {
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Shift::Raise => "↑",
+            Shift::Lower => "↓",
+            Shift::Hold => "",
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Shift::Raise => "raise",
+            Shift::Lower => "lower",
+            Shift::Hold => "hold",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "raise" => Some(Shift::Raise),
+            "lower" => Some(Shift::Lower),
+            "hold" => Some(Shift::Hold),
+            _ => None,
+        }
+    }
+}
+
+/// What the plan is built from for one hour.
+#[derive(Debug, Clone, Copy)]
+pub struct PlanInput {
+    pub timestamp: DateTime<Utc>,
+    pub price_cents_kwh: f64,
+    /// The curve setting for the hour, NaN if unknown.
+    pub base: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlanHour {
+    pub timestamp: DateTime<Utc>,
+    pub price_cents_kwh: f64,
+    pub base: f64,
+    pub setting: f64,
+    pub shift: Shift,
+}
+
+/// Move heat out of the most expensive hours. Going through the hours
+/// priciest first, each one whose dial can go a position down is paired
+/// with the cheapest hours in the `mass_hours` before it whose dials can go
+/// a position up, until at least as many dial-hours are banked as are
+/// skipped. The pairing is kept only if the banked heat costs less than the
+/// heat it replaces, so the total heat over the plan never drops.
+///
+/// `past` are the shifted hours already acted on before `hours`. Heat they
+/// banked that no past lowered hour used is spent first, at no cost, so an
+/// expensive hour can still be lowered once the cheap hours before it are
+/// over. Only `hours` are returned.
+pub fn plan(
+    past: &[PlanHour],
+    hours: &[PlanInput],
+    positions: &[f64],
+    mass_hours: usize,
+) -> Vec<PlanHour> {
+    let step_up = |base: f64| positions.iter().copied().find(|&p| p > base + 0.01);
+    let step_down = |base: f64| positions.iter().rev().copied().find(|&p| p < base - 0.01);
+    let usable = |h: &PlanInput| h.base.is_finite() && h.price_cents_kwh.is_finite();
+
+    let mut planned: Vec<PlanHour> = hours
+        .iter()
+        .map(|h| PlanHour {
+            timestamp: h.timestamp,
+            price_cents_kwh: h.price_cents_kwh,
+            base: h.base,
+            setting: h.base,
+            shift: Shift::Hold,
+        })
+        .collect();
+    let Some(first) = hours.first() else {
+        return planned;
+    };
+
+    // Heat still banked by each of the `mass_hours` before the plan, oldest
+    // first, after the past lowered hours have taken theirs.
+    let mut spare = vec![0.0; mass_hours];
+    for k in 0..mass_hours {
+        let timestamp = first.timestamp - Duration::hours((mass_hours - k) as i64);
+        let Some(h) = past.iter().find(|h| h.timestamp == timestamp) else {
+            continue;
+        };
+        match h.shift {
+            Shift::Raise => spare[k] = h.setting - h.base,
+            Shift::Lower => {
+                let mut owed = h.base - h.setting;
+                for earlier in &mut spare[..k] {
+                    let used = earlier.min(owed);
+                    *earlier -= used;
+                    owed -= used;
+                }
+            }
+            Shift::Hold => {}
+        }
+    }
+
+    let mut by_price: Vec<usize> = (0..hours.len()).filter(|&j| usable(&hours[j])).collect();
+    by_price.sort_by(|&a, &b| {
+        hours[b]
+            .price_cents_kwh
+            .total_cmp(&hours[a].price_cents_kwh)
+    });
+
+    for j in by_price {
+        if planned[j].shift != Shift::Hold {
+            continue;
+        }
+        let expensive = &hours[j];
+        let Some(lowered) = step_down(expensive.base) else {
+            continue;
+        };
+        let skipped = expensive.base - lowered;
+
+        // Past heat, by its index in `spare`, goes before any new raise
+        let banked_before: Vec<usize> = (j.min(mass_hours)..mass_hours)
+            .filter(|&k| spare[k] > 0.01)
+            .collect();
+        let mut candidates: Vec<(usize, f64)> = (j.saturating_sub(mass_hours)..j)
+            .filter(|&i| planned[i].shift == Shift::Hold && usable(&hours[i]))
+            .filter(|&i| hours[i].price_cents_kwh < expensive.price_cents_kwh)
+            .filter_map(|i| step_up(hours[i].base).map(|raised| (i, raised)))
+            .collect();
+        candidates.sort_by(|a, b| {
+            hours[a.0]
+                .price_cents_kwh
+                .total_cmp(&hours[b.0].price_cents_kwh)
+        });
+
+        let mut banked = 0.0;
+        let mut used = Vec::new();
+        for k in banked_before {
+            if banked >= skipped - 0.01 {
+                break;
+            }
+            let take = spare[k].min(skipped - banked);
+            banked += take;
+            used.push((k, take));
+        }
+        let mut cost = 0.0;
+        let mut chosen = Vec::new();
+        for (i, raised) in candidates {
+            if banked >= skipped - 0.01 {
+                break;
+            }
+            banked += raised - hours[i].base;
+            cost += (raised - hours[i].base) * hours[i].price_cents_kwh;
+            chosen.push((i, raised));
+        }
+        if banked < skipped - 0.01 || cost >= skipped * expensive.price_cents_kwh {
+            continue;
+        }
+
+        planned[j].setting = lowered;
+        planned[j].shift = Shift::Lower;
+        for (k, take) in used {
+            spare[k] -= take;
+        }
+        for (i, raised) in chosen {
+            planned[i].setting = raised;
+            planned[i].shift = Shift::Raise;
+        }
+    }
+    planned
+}
+
+/// Hourly means of the stored 15-minute total prices, keyed by the hour's
+/// timestamp.
+async fn hourly_prices(
+    db: &db::Db,
+    components: &PriceComponents,
+    tz: Tz,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<HashMap<i64, f64>> {
+    let prices = db
+        .get_electricity_prices(
+            &from.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            &to.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        )
+        .await?;
+    let mut hourly: HashMap<i64, (f64, usize)> = HashMap::new();
+    for p in &prices {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(&p.timestamp) {
+            let entry = hourly
+                .entry(dt.timestamp() / 3600 * 3600)
+                .or_insert((0.0, 0));
+            entry.0 += components.total(p.price_cents_kwh, dt.to_utc(), tz);
+            entry.1 += 1;
+        }
+    }
+    Ok(hourly
+        .into_iter()
+        .map(|(h, (sum, count))| (h, sum / count as f64))
+        .collect())
+}
+
+/// Plan `radiator` over the hours of `forecast` (which starts at the current
+/// hour) that have a price. Each hour's base is the curve setting for the
+/// effective weighted average from that hour on, like the recommendation.
+/// The shifted hours the scheduler already acted on carry into the plan.
+/// Empty when `THERMAL_MASS_HOURS` isn't configured.
+pub async fn load_plan(
+    db: &db::Db,
+    config: &Config,
+    location: &db::Location,
+    radiator: &db::Radiator,
+    curve: &HeatingCurve,
+    forecast: &[ForecastPoint],
+) -> Result<Vec<PlanHour>> {
+    let Some(mass_hours) = config.thermal_mass_hours else {
+        return Ok(Vec::new());
+    };
+    let Some(first) = forecast.first() else {
+        return Ok(Vec::new());
+    };
+    let from = first.timestamp;
+    let tz = location.tz();
+    let prices = hourly_prices(
+        db,
+        &config.price_components,
+        tz,
+        from,
+        from + Duration::hours(HORIZON_HOURS as i64),
+    )
+    .await?;
+
+    let adjustment = config.temperature_adjustment();
+    let inputs: Vec<PlanInput> = forecast
+        .iter()
+        .take(HORIZON_HOURS)
+        .enumerate()
+        .map_while(|(k, p)| {
+            let price = prices.get(&(p.timestamp.timestamp() / 3600 * 3600))?;
+            let effective = adjustment.weighted_avg(&forecast[k..], tz, 0.9, 24, 3);
+            Some(PlanInput {
+                timestamp: p.timestamp,
+                price_cents_kwh: *price,
+                base: curve.setting_for(effective - radiator.curve_offset_c),
+            })
+        })
+        .collect();
+    let since = from - Duration::hours(mass_hours as i64);
+    let past: Vec<PlanHour> = db
+        .list_preheat_hours(radiator.id, &since.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .await?
+        .iter()
+        .filter_map(|h| {
+            Some(PlanHour {
+                timestamp: DateTime::parse_from_rfc3339(&h.timestamp).ok()?.to_utc(),
+                price_cents_kwh: f64::NAN,
+                base: h.base,
+                setting: h.setting,
+                shift: Shift::parse(&h.shift)?,
+            })
+        })
+        .collect();
+    Ok(plan(&past, &inputs, &curve.settings(), mass_hours))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSITIONS: [f64; 5] = [1.0, 1.5, 2.0, 2.5, 3.0];
+
+    fn inputs(prices: &[f64]) -> Vec<PlanInput> {
+        let start = DateTime::from_timestamp(1_760_000_400, 0).unwrap();
+        prices
+            .iter()
+            .enumerate()
+            .map(|(k, &price_cents_kwh)| PlanInput {
+                timestamp: start + Duration::hours(k as i64),
+                price_cents_kwh,
+                base: 2.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expensive_hour_after_cheap_ones_is_lowered_when_it_comes() {
+        let hours = inputs(&[5.0, 5.0, 5.0, 30.0, 5.0, 5.0]);
+        let mut acted_on: Vec<PlanHour> = Vec::new();
+        for now in 0..hours.len() {
+            // Replanned every hour from the current hour, like the scheduler
+            let planned = plan(&acted_on, &hours[now..], &POSITIONS, 3);
+            acted_on.push(planned[0]);
+        }
+        let shifts: Vec<Shift> = acted_on.iter().map(|h| h.shift).collect();
+        assert_eq!(
+            shifts,
+            [
+                Shift::Raise,
+                Shift::Hold,
+                Shift::Hold,
+                Shift::Lower,
+                Shift::Hold,
+                Shift::Hold
+            ]
+        );
+        assert_eq!(acted_on[0].setting, 2.5);
+        assert_eq!(acted_on[3].setting, 1.5);
+    }
+
+    #[test]
+    fn banked_heat_is_used_only_once() {
+        let hours = inputs(&[5.0, 30.0, 30.0]);
+        let past = [
+            PlanHour {
+                timestamp: hours[0].timestamp - Duration::hours(2),
+                price_cents_kwh: f64::NAN,
+                base: 2.0,
+                setting: 2.5,
+                shift: Shift::Raise,
+            },
+            PlanHour {
+                timestamp: hours[0].timestamp - Duration::hours(1),
+                price_cents_kwh: f64::NAN,
+                base: 2.0,
+                setting: 1.5,
+                shift: Shift::Lower,
+            },
+        ];
+        let planned = plan(&past, &hours, &POSITIONS, 3);
+        let shifts: Vec<Shift> = planned.iter().map(|h| h.shift).collect();
+        // The past raise paid for the past lower, so only the cheap first
+        // hour can bank heat for one of the expensive ones
+        assert_eq!(shifts, [Shift::Raise, Shift::Lower, Shift::Hold]);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::electricity::PriceComponents;
+
+/// At most this many below-threshold periods are listed in one alert.
+const MAX_PERIODS: usize = 3;
+
+/// Price alerts configured in the environment. The threshold and peak
+/// alerts compare total prices, the negative price alert spot prices, which
+/// are what goes negative.
+#[derive(Clone, Debug, Default)]
+pub struct PriceAlerts {
+    pub below_cents: Option<f64>,
+    pub negative: bool,
+    pub peak_above_cents: Option<f64>,
+}
+
+impl PriceAlerts {
+    pub fn is_configured(&self) -> bool {
+        self.below_cents.is_some() || self.negative || self.peak_above_cents.is_some()
+    }
+}
+
+/// An alert for one local day. `kind` and `date` are its `notification_log`
+/// key, so each kind is sent at most once a day.
+pub struct Alert {
+    pub kind: &'static str,
+    pub date: NaiveDate,
+    pub message: String,
+}
+
+struct Slot {
+    start: DateTime<Utc>,
+    spot_cents: f64,
+    total_cents: f64,
+}
+
+/// Runs of consecutive slots, as `HH:MM–HH:MM` in local time.
+fn periods(slots: &[&Slot], tz: Tz) -> Vec<String> {
+    let quarter = Duration::minutes(15);
+    let mut runs: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for slot in slots {
+        match runs.last_mut() {
+            Some((_, end)) if *end == slot.start => *end = slot.start + quarter,
+            _ => runs.push((slot.start, slot.start + quarter)),
+        }
+    }
+    runs.iter()
+        .map(|(start, end)| {
+            format!(
+                "{}–{}",
+                start.with_timezone(&tz).format("%H:%M"),
+                end.with_timezone(&tz).format("%H:%M")
+            )
+        })
+        .collect()
+}
+
+fn day_label(date: NaiveDate, today: NaiveDate) -> String {
+    if date == today {
+        "today".to_string()
+    } else if date == today + Duration::days(1) {
+        "tomorrow".to_string()
+    } else {
+        date.format("%a %-d %b").to_string()
+    }
+}
+
+fn join_periods(periods: &[String]) -> String {
+    let mut listed = periods[..periods.len().min(MAX_PERIODS)].join(", ");
+    if periods.len() > MAX_PERIODS {
+        listed.push_str(&format!(" and {} more", periods.len() - MAX_PERIODS));
+    }
+    listed
+}
+
+/// Alerts triggered by `prices` (spot, as fetched) from the current slot on,
+/// per local day.
+pub fn evaluate(
+    alerts: &PriceAlerts,
+    prices: &[(String, f64)],
+    components: &PriceComponents,
+    tz: Tz,
+    now: DateTime<Utc>,
+) -> Vec<Alert> {
+    let current_slot = now - Duration::minutes(15);
+    let mut days: BTreeMap<NaiveDate, Vec<Slot>> = BTreeMap::new();
+    for (timestamp, spot_cents) in prices {
+        let Ok(start) = DateTime::parse_from_rfc3339(timestamp).map(|dt| dt.to_utc()) else {
+            continue;
+        };
+        if start <= current_slot {
+            continue;
+        }
+        days.entry(start.with_timezone(&tz).date_naive())
+            .or_default()
+            .push(Slot {
+                start,
+                spot_cents: *spot_cents,
+                total_cents: components.total(*spot_cents, start, tz),
+            });
+    }
+
+    let today = now.with_timezone(&tz).date_naive();
+    let local = |dt: DateTime<Utc>| dt.with_timezone(&tz).format("%H:%M").to_string();
+    let mut triggered = Vec::new();
+    for (date, mut slots) in days {
+        slots.sort_by_key(|s| s.start);
+        let day = day_label(date, today);
+
+        if alerts.negative {
+            let negative: Vec<&Slot> = slots.iter().filter(|s| s.spot_cents < 0.0).collect();
+            if let Some(lowest) = negative
+                .iter()
+                .min_by(|a, b| a.spot_cents.total_cmp(&b.spot_cents))
+            {
+                triggered.push(Alert {
+                    kind: "price_negative",
+                    date,
+                    message: format!(
+                        "Negative electricity prices {day} {}, lowest {:.2} snt at {}",
+                        join_periods(&periods(&negative, tz)),
+                        lowest.spot_cents,
+                        local(lowest.start)
+                    ),
+                });
+            }
+        }
+
+        if let Some(threshold) = alerts.below_cents {
+            let cheap: Vec<&Slot> = slots.iter().filter(|s| s.total_cents < threshold).collect();
+            if let Some(lowest) = cheap
+                .iter()
+                .min_by(|a, b| a.total_cents.total_cmp(&b.total_cents))
+            {
+                triggered.push(Alert {
+                    kind: "price_below",
+                    date,
+                    message: format!(
+                        "Electricity below {threshold} snt {day} {}, lowest {:.2} snt at {}",
+                        join_periods(&periods(&cheap, tz)),
+                        lowest.total_cents,
+                        local(lowest.start)
+                    ),
+                });
+            }
+        }
+
+        if let Some(threshold) = alerts.peak_above_cents {
+            // The first of equally expensive slots
+            let peak = slots.iter().reduce(|peak, s| {
+                if s.total_cents > peak.total_cents {
+                    s
+                } else {
+                    peak
+                }
+            });
+            if let Some(peak) = peak.filter(|p| p.total_cents > threshold) {
+                triggered.push(Alert {
+                    kind: "price_peak",
+                    date,
+                    message: format!(
+                        "Electricity peaks at {:.2} snt {day} at {}, above {threshold} snt",
+                        peak.total_cents,
+                        local(peak.start)
+                    ),
+                });
+            }
+        }
+    }
+    triggered
+}
@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Query, State},
+    response::{Html, Json},
+};
+use chrono::Utc;
+use http::StatusCode;
+use hypertext::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    accuracy::{self, AccuracyReport, ErrorStats},
+    routes::index::error_page,
+    AppState,
+};
+
+const DEFAULT_DAYS: i64 = 30;
+/// Longest history a report covers, about ten years.
+const MAX_DAYS: i64 = 3650;
+
+#[derive(Deserialize)]
+pub struct AccuracyQuery {
+    pub days: Option<i64>,
+}
+
+/// The requested number of days, at least one, or an error above `MAX_DAYS`.
+fn report_days(query: &AccuracyQuery) -> Result<i64, String> {
+    let days = query.days.unwrap_or(DEFAULT_DAYS).max(1);
+    if days > MAX_DAYS {
+        return Err(format!("days must be at most {MAX_DAYS}"));
+    }
+    Ok(days)
+}
+
+async fn load_report(state: &AppState, days: i64) -> anyhow::Result<AccuracyReport> {
+    let from = (Utc::now() - chrono::Duration::days(days))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let pairs = state.db.get_forecast_observation_pairs(&from).await?;
+    Ok(accuracy::compute(&pairs, days))
+}
+
+pub async fn json(
+    State(state): State<AppState>,
+    Query(query): Query<AccuracyQuery>,
+) -> Result<Json<AccuracyReport>, (StatusCode, String)> {
+    let days = report_days(&query).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    load_report(&state, days)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}")))
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Query(query): Query<AccuracyQuery>,
+) -> Html<String> {
+    let days = match report_days(&query) {
+        Ok(days) => days,
+        Err(e) => return Html(error_page(&e)),
+    };
+    let report = match load_report(&state, days).await {
+        Ok(r) => r,
+        Err(e) => {
+            return Html(error_page(&format!("Failed to compute accuracy: {e}")));
+        }
+    };
+
+    let fmt = |s: &ErrorStats, unit: &str| -> (String, String) {
+        match (s.bias, s.mae) {
+            (Some(bias), Some(mae)) => {
+                (format!("{:+.1} {unit}", bias), format!("{:.1} {unit}", mae))
+            }
+            _ => ("-".into(), "-".into()),
+        }
+    };
+
+    Html(rsx! {
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title> "Weather – forecast accuracy" </title>
+            <link rel="manifest" href="/manifest.json">
+            <meta name="theme-color" content="#000">
+            <link rel="stylesheet" href="/assets/styles.css">
+        </head>
+        <body class="bg-gray-1 text-gray-12 text-sm p-4 max-w-[37.5rem] mx-auto">
+            <h1 class="mb-1 text-gray-12 text-base"> "Forecast accuracy" </h1>
+            <p class="text-gray-11 text-xs mb-4">
+                "Last " (days) " days · bias = forecast − observed · "
+                <a href=(format!("/api/accuracy?days={days}")) class="text-gray-11"> "JSON" </a>
+            </p>
+
+            <div class="overflow-x-auto">
+                <table class="w-full text-sm">
+                    <thead>
+                        <tr class="bg-gray-2">
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">Lead</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">"Temp bias"</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">"Temp MAE"</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">"Wind bias"</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">"Wind MAE"</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">"Precip bias"</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">"Precip MAE"</th>
+                            <th class="px-3 py-1.5 text-left font-medium text-gray-11">n</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        @for lead in &report.leads {
+                            @let (temp_bias, temp_mae) = fmt(&lead.temperature_c, "°C");
+                            @let (wind_bias, wind_mae) = fmt(&lead.wind_speed_ms, "m/s");
+                            @let (precip_bias, precip_mae) = fmt(&lead.precipitation_mm, "mm");
+                            <tr class="even:bg-gray-2">
+                                <td class="px-3 py-1.5"> (format!("≤{}h", lead.lead_hours)) </td>
+                                <td class="px-3 py-1.5"> (temp_bias) </td>
+                                <td class="px-3 py-1.5"> (temp_mae) </td>
+                                <td class="px-3 py-1.5"> (wind_bias) </td>
+                                <td class="px-3 py-1.5"> (wind_mae) </td>
+                                <td class="px-3 py-1.5"> (precip_bias) </td>
+                                <td class="px-3 py-1.5"> (precip_mae) </td>
+                                <td class="px-3 py-1.5 text-gray-11"> (lead.temperature_c.count) </td>
+                            </tr>
+                        }
+                    </tbody>
+                </table>
+            </div>
+
+            <div class="flex gap-2 mt-8">
+                <a href="/" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">Back</a>
+                @for d in [7_i64, 30, 90, 365] {
+                    <a href=(format!("/accuracy?days={d}")) class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline"> (format!("{d}d")) </a>
+                }
+            </div>
+        </body>
+        </html>
+    }.render().into_inner())
+}
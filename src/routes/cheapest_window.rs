@@ -0,0 +1,222 @@
+use axum::{
+    extract::{Query, State},
+    response::{Html, Json},
+};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use http::StatusCode;
+use hypertext::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    cheapest_window::{self, WindowReport},
+    routes::index::error_page,
+    AppState,
+};
+
+/// Form fields are strings so that empty inputs mean "not given".
+#[derive(Deserialize)]
+pub struct WindowQuery {
+    pub duration: Option<String>,
+    /// Earliest start, local `YYYY-MM-DDTHH:MM` or RFC 3339.
+    pub from: Option<String>,
+    /// Latest end, local `YYYY-MM-DDTHH:MM` or RFC 3339.
+    pub to: Option<String>,
+    pub power_kw: Option<String>,
+}
+
+struct Params {
+    duration: Duration,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    power_kw: Option<f64>,
+}
+
+fn given(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn parse_time(s: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.to_utc());
+    }
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").ok()?;
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.to_utc())
+}
+
+/// `Ok(None)` when no duration was entered yet.
+fn parse_query(query: &WindowQuery, tz: Tz) -> Result<Option<Params>, String> {
+    let Some(duration) = given(&query.duration) else {
+        return Ok(None);
+    };
+    let duration = cheapest_window::parse_duration(duration).ok_or_else(|| {
+        format!(
+            "Invalid duration `{duration}`, use e.g. 2h15m, 6h or 45m, at most {}h",
+            cheapest_window::MAX_DURATION_HOURS
+        )
+    })?;
+    let time = |value: &Option<String>| -> Result<Option<DateTime<Utc>>, String> {
+        given(value)
+            .map(|v| parse_time(v, tz).ok_or_else(|| format!("Invalid time `{v}`")))
+            .transpose()
+    };
+    let power_kw = given(&query.power_kw)
+        .map(|v| {
+            v.replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|kw| kw.is_finite() && *kw > 0.0)
+                .ok_or_else(|| format!("Invalid power `{v}`"))
+        })
+        .transpose()?;
+    Ok(Some(Params {
+        duration,
+        from: time(&query.from)?,
+        to: time(&query.to)?,
+        power_kw,
+    }))
+}
+
+async fn load_report(state: &AppState, params: &Params) -> anyhow::Result<Option<WindowReport>> {
+    cheapest_window::find(
+        &state.db,
+        &state.config,
+        params.duration,
+        params.from,
+        params.to,
+        params.power_kw,
+    )
+    .await
+}
+
+pub async fn json(
+    State(state): State<AppState>,
+    Query(query): Query<WindowQuery>,
+) -> Result<Json<WindowReport>, (StatusCode, String)> {
+    let params = parse_query(&query, state.config.tz)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .ok_or((StatusCode::BAD_REQUEST, "duration is required".to_string()))?;
+    load_report(&state, &params)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}")))?
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            "No prices cover a run of that length in the window".to_string(),
+        ))
+}
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Query(query): Query<WindowQuery>,
+) -> Html<String> {
+    let tz = state.config.tz;
+    let (report, problem) = match parse_query(&query, tz) {
+        Ok(Some(params)) => match load_report(&state, &params).await {
+            Ok(Some(report)) => (Some(report), None),
+            Ok(None) => (
+                None,
+                Some("No prices cover a run of that length in the window.".to_string()),
+            ),
+            Err(e) => {
+                return Html(error_page(&format!(
+                    "Failed to find the cheapest window: {e}"
+                )));
+            }
+        },
+        Ok(None) => (None, None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let value = |v: &Option<String>| v.clone().unwrap_or_default();
+    let local = |dt: DateTime<Utc>| dt.with_timezone(&tz).format("%a %H:%M").to_string();
+    let json_href = format!(
+        "/api/cheapest-window?duration={}&from={}&to={}&power_kw={}",
+        value(&query.duration),
+        value(&query.from),
+        value(&query.to),
+        value(&query.power_kw)
+    );
+
+    Html(rsx! {
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title> "Weather – cheapest window" </title>
+            <link rel="manifest" href="/manifest.json">
+            <meta name="theme-color" content="#000">
+            <link rel="stylesheet" href="/assets/styles.css">
+        </head>
+        <body class="bg-gray-1 text-gray-12 text-sm p-4 max-w-[37.5rem] mx-auto">
+            <h1 class="mb-1 text-gray-12 text-base"> "Cheapest window" </h1>
+            <p class="text-gray-11 text-xs mb-4">
+                "Cheapest contiguous start for a run, at total prices · times in " (tz.name())
+            </p>
+
+            <form method="GET" action="/cheapest-window" class="flex flex-col gap-2">
+                <label class="text-gray-11 text-xs"> "Duration, e.g. 2h15m or 6h" </label>
+                <input name="duration" value=(value(&query.duration)) placeholder="2h15m" required class="focus2 bg-gray-a3 px-3 py-2">
+                <div class="flex gap-2">
+                    <div class="flex flex-col gap-2 flex-1">
+                        <label class="text-gray-11 text-xs"> "Start no earlier than" </label>
+                        <input type="datetime-local" name="from" value=(value(&query.from)) class="focus2 bg-gray-a3 px-3 py-2">
+                    </div>
+                    <div class="flex flex-col gap-2 flex-1">
+                        <label class="text-gray-11 text-xs"> "Done by" </label>
+                        <input type="datetime-local" name="to" value=(value(&query.to)) class="focus2 bg-gray-a3 px-3 py-2">
+                    </div>
+                </div>
+                <label class="text-gray-11 text-xs"> "Power kW, for the cost (optional)" </label>
+                <input name="power_kw" value=(value(&query.power_kw)) inputmode="decimal" class="focus2 bg-gray-a3 px-3 py-2">
+                <button type="submit" class="focus py-3 px-4 bg-gray-a4 text-gray-12 font-medium"> "Find" </button>
+            </form>
+
+            @if let Some(problem) = &problem {
+                <p class="mt-6 text-gray-11"> (problem) </p>
+            }
+
+            @if let Some(report) = &report {
+                @let cheapest = &report.cheapest;
+                <div class="mt-6 flex flex-col gap-1">
+                    <p class="text-base">
+                        "Start " <span class="bg-gray-a5 px-0.5 -mx-0.5"> (local(cheapest.start)) </span>
+                        ", done " (local(cheapest.end))
+                    </p>
+                    <p>
+                        (format!("{:.2} snt/kWh average", cheapest.avg_price_cents_kwh))
+                        @if let Some(cost) = cheapest.cost_cents {
+                            (format!(" · {:.1} snt", cost))
+                        }
+                    </p>
+                    @if let (Some(now), Some(savings)) = (&report.now, report.savings_cents_kwh) {
+                        <p class="text-gray-11">
+                            (format!("Starting now: {:.2} snt/kWh", now.avg_price_cents_kwh))
+                            @if let (Some(now_cost), Some(cost)) = (now.cost_cents, cheapest.cost_cents) {
+                                (format!(" · {:.1} snt, {:.1} snt saved", now_cost, now_cost - cost))
+                            } @else {
+                                (format!(", {:.2} snt/kWh more", savings))
+                            }
+                        </p>
+                    } @else {
+                        <p class="text-gray-11"> "No prices for starting now." </p>
+                    }
+                    <p class="text-gray-11 text-xs">
+                        <a href=(json_href) class="text-gray-11"> "JSON" </a>
+                    </p>
+                </div>
+            }
+
+            <div class="flex gap-2 mt-8 flex-wrap">
+                <a href="/" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">Back</a>
+                @for (label, duration) in [("Dishwasher 2h15m", "2h15m"), ("Laundry 1h30m", "1h30m"), ("EV 6h", "6h")] {
+                    <a href=(format!("/cheapest-window?duration={duration}")) class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline"> (label) </a>
+                }
+            </div>
+        </body>
+        </html>
+    }.render().into_inner())
+}
@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    response::Html,
+};
+use chrono::{Month, Utc};
+use http::StatusCode;
+use hypertext::prelude::*;
+
+use crate::{
+    degree_days::{self, MonthlyDegreeDays},
+    routes::index::error_page,
+    AppState,
+};
+
+pub async fn handler(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Html<String>, (StatusCode, Html<String>)> {
+    let location = match state.db.get_location(&slug).await {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Html(error_page(&format!("Unknown location {slug}"))),
+            ));
+        }
+        Err(e) => return Ok(Html(error_page(&format!("Failed to load location: {e}")))),
+    };
+
+    let months = match degree_days::load_monthly(&state.db, &location).await {
+        Ok(m) => m,
+        Err(e) => {
+            return Ok(Html(error_page(&format!(
+                "Failed to load degree days: {e}"
+            ))));
+        }
+    };
+
+    // Newest year first so the current year sits next to the month names.
+    let mut years: Vec<i32> = months.iter().map(|m| m.year).collect();
+    years.dedup();
+    years.reverse();
+
+    let cell = |year: i32, month: u32| months.iter().find(|m| m.year == year && m.month == month);
+    let year_total = |year: i32| -> f64 {
+        months
+            .iter()
+            .filter(|m| m.year == year)
+            .map(|m| m.hdd)
+            .sum()
+    };
+    let fmt_cell = |m: Option<&MonthlyDegreeDays>| -> (String, String, &'static str) {
+        match m {
+            Some(m) => (
+                format!("{:.0}", m.hdd),
+                format!("{} of {} days", m.days, m.days_in_month),
+                if m.complete() {
+                    "px-3 py-1.5 text-right"
+                } else {
+                    "px-3 py-1.5 text-right text-gray-11"
+                },
+            ),
+            None => (
+                "-".into(),
+                String::new(),
+                "px-3 py-1.5 text-right text-gray-9",
+            ),
+        }
+    };
+    let current_year = Utc::now()
+        .with_timezone(&location.tz())
+        .format("%Y")
+        .to_string();
+
+    Ok(Html(rsx! {
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title> "Weather – degree days" </title>
+            <link rel="manifest" href="/manifest.json">
+            <meta name="theme-color" content="#000">
+            <link rel="stylesheet" href="/assets/styles.css">
+        </head>
+        <body class="bg-gray-1 text-gray-12 text-sm p-4 max-w-[37.5rem] mx-auto">
+            <h1 class="mb-1 text-gray-12 text-base"> "Heating degree days · " (location.place_label().to_string()) </h1>
+            <p class="text-gray-11 text-xs mb-4">
+                "Base " (format!("{:.0}", degree_days::BASE_TEMP_C)) " °C · days count below "
+                (format!("{:.0}", degree_days::SPRING_THRESHOLD_C)) " °C (Jan–Jun) or "
+                (format!("{:.0}", degree_days::AUTUMN_THRESHOLD_C)) " °C (Jul–Dec) · grey = partial month"
+            </p>
+
+            @if years.is_empty() {
+                <p class="text-gray-11"> "No complete days of observations yet." </p>
+            } @else {
+                <div class="overflow-x-auto">
+                    <table class="w-full text-sm">
+                        <thead>
+                            <tr class="bg-gray-2">
+                                <th class="px-3 py-1.5 text-left font-medium text-gray-11">Month</th>
+                                @for year in &years {
+                                    <th class="px-3 py-1.5 text-right font-medium text-gray-11"> (year) </th>
+                                }
+                            </tr>
+                        </thead>
+                        <tbody>
+                            @for month in 1..=12u32 {
+                                <tr class="even:bg-gray-2">
+                                    <td class="px-3 py-1.5"> (Month::try_from(month as u8).map(|m| m.name()).unwrap_or_default()) </td>
+                                    @for year in &years {
+                                        @let (value, title, class) = fmt_cell(cell(*year, month));
+                                        <td class=(class) title=(title)> (value) </td>
+                                    }
+                                </tr>
+                            }
+                            <tr class="border-t border-gray-6 font-medium">
+                                <td class="px-3 py-1.5"> "Total" </td>
+                                @for year in &years {
+                                    <td class=(if year.to_string() == current_year { "px-3 py-1.5 text-right text-gray-11" } else { "px-3 py-1.5 text-right" })>
+                                        (format!("{:.0}", year_total(*year)))
+                                    </td>
+                                }
+                            </tr>
+                        </tbody>
+                    </table>
+                </div>
+            }
+
+            <div class="flex gap-2 mt-8">
+                <a href=(format!("/l/{}", location.slug)) class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">Back</a>
+            </div>
+        </body>
+        </html>
+    }.render().into_inner()))
+}
@@ -1,13 +1,18 @@
 use axum::{
-    extract::{Form, State},
+    extract::{Form, Path, State},
     response::{Html, Redirect},
 };
 use chrono::{NaiveDate, TimeZone, Utc};
+use http::StatusCode;
 use hypertext::prelude::*;
 use std::collections::{BTreeMap, HashMap};
 
 use crate::{
-    weather::{self, temp_to_radiator_setting, ForecastPoint},
+    db::{self, RadiatorChange},
+    heating::{self, SettingSource},
+    preheat::{self, PlanHour, Shift},
+    scheduler,
+    weather::ForecastPoint,
     AppState,
 };
 
@@ -15,7 +20,15 @@ struct HourRow {
     timestamp: chrono::DateTime<Utc>,
     temperature_c: f64,
     wind_speed_ms: f64,
+    wind_gust_ms: f64,
     precipitation_mm: f64,
+    weather_symbol: f64,
+}
+
+struct RadiatorRow {
+    radiator: db::Radiator,
+    recommended_setting: f64,
+    current_setting: Option<f64>,
 }
 
 struct DayGroup {
@@ -29,11 +42,25 @@ struct DayGroup {
     avg_price: f64,
 }
 
-pub async fn handler(State(state): State<AppState>) -> Html<String> {
-    let forecast = match weather::fetch_forecast(&state.config.fmi_sid).await {
+pub async fn handler(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Html<String>, (StatusCode, Html<String>)> {
+    let location = match state.db.get_location(&slug).await {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Html(error_page(&format!("Unknown location {slug}"))),
+            ));
+        }
+        Err(e) => return Ok(Html(error_page(&format!("Failed to load location: {e}")))),
+    };
+
+    let forecast = match scheduler::load_forecast(&state.db, &state.config, &location).await {
         Ok(f) => f,
         Err(e) => {
-            return Html(error_page(&format!("Failed to fetch forecast: {e}")));
+            return Ok(Html(error_page(&format!("Failed to fetch forecast: {e}"))));
         }
     };
 
@@ -43,7 +70,7 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
     };
 
     let now = Utc::now();
-    let tz = state.config.tz;
+    let tz = location.tz();
     let today = now.with_timezone(&tz).date_naive();
     let tomorrow = today + chrono::Duration::days(1);
 
@@ -54,7 +81,7 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
     let obs_to = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
     let mut observations = state
         .db
-        .get_weather_observations(&obs_from, &obs_to)
+        .get_weather_observations(location.id, &obs_from, &obs_to)
         .await
         .unwrap_or_default();
     tracing::info!(
@@ -64,37 +91,60 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
         obs_to
     );
     if observations.is_empty() {
-        match weather::fetch_observations(&state.config.fmi_sid).await {
-            Ok(points) => {
-                tracing::info!("Fetched {} observation points from FMI", points.len());
-                if let Err(e) = state.db.upsert_weather_observations(&points).await {
-                    tracing::error!("Failed to upsert observations: {e}");
-                }
-                if let Some(wind_sid) = &state.config.fmi_sid_wind {
-                    match weather::fetch_observations(wind_sid).await {
-                        Ok(wind_points) => {
-                            if let Err(e) = state.db.merge_wind_observations(&wind_points).await {
-                                tracing::error!("Failed to merge wind observations: {e}");
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to fetch wind observations: {e}");
-                        }
-                    }
-                }
+        match scheduler::refresh_observations(&state.db, &state.config, &location).await {
+            Ok(()) => {
                 observations = state
                     .db
-                    .get_weather_observations(&obs_from, &obs_to)
+                    .get_weather_observations(location.id, &obs_from, &obs_to)
                     .await
                     .unwrap_or_default();
                 tracing::info!("After upsert, observations from DB: {}", observations.len());
             }
             Err(e) => {
-                tracing::error!("Failed to fetch observations from FMI: {e}");
+                tracing::error!("Failed to fetch observations: {e}");
             }
         }
     }
 
+    // Indoor sensor readings, averaged per hour
+    let indoor_hourly: HashMap<i64, f64> = state
+        .db
+        .get_indoor_hourly(
+            location.id,
+            &obs_from,
+            &(now + chrono::Duration::hours(1))
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
+        )
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(hour, temp)| {
+            let dt = chrono::DateTime::parse_from_rfc3339(&hour).ok()?;
+            Some((dt.timestamp(), temp))
+        })
+        .collect();
+    let has_indoor = !indoor_hourly.is_empty();
+
+    // Radiator setting changes, shown next to the hour they happened in
+    let mut radiator_changes: HashMap<i64, Vec<RadiatorChange>> = HashMap::new();
+    for change in state
+        .db
+        .get_radiator_history(
+            &obs_from,
+            &(now + chrono::Duration::hours(1))
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
+        )
+        .await
+        .unwrap_or_default()
+    {
+        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&change.changed_at) {
+            let hour_ts = dt.timestamp() - (dt.timestamp() % 3600);
+            radiator_changes.entry(hour_ts).or_default().push(change);
+        }
+    }
+
     // Merge observations + forecast into a BTreeMap keyed by hour timestamp.
     // Observations take priority on overlap.
     let mut timeline: BTreeMap<i64, HourRow> = BTreeMap::new();
@@ -108,7 +158,9 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                 timestamp: p.timestamp,
                 temperature_c: p.temperature_c,
                 wind_speed_ms: p.wind_speed_ms,
+                wind_gust_ms: p.wind_gust_ms,
                 precipitation_mm: p.precipitation_mm,
+                weather_symbol: p.weather_symbol,
             },
         );
     }
@@ -123,8 +175,10 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                 HourRow {
                     timestamp: utc,
                     temperature_c: o.temperature_c,
-                    wind_speed_ms: o.wind_speed_ms,
-                    precipitation_mm: o.precipitation_mm,
+                    wind_speed_ms: o.wind_speed_ms.unwrap_or(f64::NAN),
+                    wind_gust_ms: o.wind_gust_ms.unwrap_or(f64::NAN),
+                    precipitation_mm: o.precipitation_mm.unwrap_or(f64::NAN),
+                    weather_symbol: o.weather_symbol.unwrap_or(f64::NAN),
                 },
             );
         }
@@ -160,12 +214,18 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
         .await
         .unwrap_or_default();
 
-    // Hourly prices for table display
+    // Hourly total prices for table display, with the spot price alongside
+    // when price components are configured
+    let components = &state.config.price_components;
     let mut hourly_prices: HashMap<i64, (f64, usize)> = HashMap::new();
+    let mut hourly_spot: HashMap<i64, (f64, usize)> = HashMap::new();
     for p in &electricity_prices {
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&p.timestamp) {
             let hour_ts = dt.to_utc().timestamp() - (dt.to_utc().timestamp() % 3600);
             let entry = hourly_prices.entry(hour_ts).or_insert((0.0, 0));
+            entry.0 += components.total(p.price_cents_kwh, dt.to_utc(), tz);
+            entry.1 += 1;
+            let entry = hourly_spot.entry(hour_ts).or_insert((0.0, 0));
             entry.0 += p.price_cents_kwh;
             entry.1 += 1;
         }
@@ -259,15 +319,63 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
     };
 
     let weighted_avg = ForecastPoint::weighted_avg_temperature(&forecast, 0.9, 24, 3);
-    let recommended_setting = temp_to_radiator_setting(weighted_avg);
-    let current_radiator = state.db.get_radiator_setting().await.ok().flatten();
+    let curve = state.db.get_heating_curve().await.unwrap_or_default();
+    let effective_avg = state
+        .config
+        .temperature_adjustment()
+        .weighted_avg(&forecast, tz, 0.9, 24, 3);
+    let radiator_settings = curve.settings();
+    // Radiators are in the home, advised and notified from its forecast
+    let home = state.db.default_location().await.ok();
+    let is_home = home.as_ref().is_some_and(|h| h.id == location.id);
+    let home_radiators = if is_home {
+        state.db.list_radiators().await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let mut radiators = Vec::new();
+    let mut preheat_hours: HashMap<i64, Vec<(String, PlanHour)>> = HashMap::new();
+    for radiator in home_radiators {
+        let recommended_setting =
+            scheduler::radiator_recommendation(&state.db, &state.config, &radiator, effective_avg)
+                .await
+                .map(|r| r.setting)
+                .unwrap_or(f64::NAN);
+        let current_setting = state
+            .db
+            .get_radiator_setting(radiator.id)
+            .await
+            .ok()
+            .flatten();
+        let plan = preheat::load_plan(
+            &state.db,
+            &state.config,
+            &location,
+            &radiator,
+            &curve,
+            &forecast,
+        )
+        .await
+        .unwrap_or_default();
+        for hour in plan.into_iter().filter(|h| h.shift != Shift::Hold) {
+            preheat_hours
+                .entry(hour.timestamp.timestamp())
+                .or_default()
+                .push((radiator.name.clone(), hour));
+        }
+        radiators.push(RadiatorRow {
+            radiator,
+            recommended_setting,
+            current_setting,
+        });
+    }
 
-    let place = &state.config.fmi_sid;
+    let place = location.place_label().to_string();
 
     // Current price: find the 15-min slot containing now
     let now_ts = now.timestamp();
     let current_slot = now_ts - (now_ts % 900);
-    let current_price = electricity_prices.iter().find_map(|p| {
+    let current_spot = electricity_prices.iter().find_map(|p| {
         let dt = chrono::DateTime::parse_from_rfc3339(&p.timestamp).ok()?;
         if dt.to_utc().timestamp() == current_slot {
             Some(p.price_cents_kwh)
@@ -275,6 +383,7 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
             None
         }
     });
+    let current_price = current_spot.map(|p| components.total(p, now, tz));
 
     // Today's price stats
     let today_start = today.and_hms_opt(0, 0, 0).unwrap();
@@ -326,7 +435,7 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
             (sum / *count as f64, local.format("%H:%M").to_string())
         });
 
-    Html(rsx! {
+    Ok(Html(rsx! {
         <!DOCTYPE html>
         <html lang="en">
         <head>
@@ -354,9 +463,17 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                     }
                     _ => "-".into()
                 };
-                <p> <span class="bg-gray-a5 px-0.5 -mx-0.5"> (current_s) " snt" </span> " now, avg " (avg_p_s) " | " (range_s) " snt" </p>
+                @let spot_s = match current_spot {
+                    Some(p) if components.is_configured() => format!(" (spot {:.1})", p),
+                    _ => String::new(),
+                };
+                <p> <span class="bg-gray-a5 px-0.5 -mx-0.5"> (current_s) " snt" </span> " now" (spot_s) ", avg " (avg_p_s) " | " (range_s) " snt" </p>
             </div>
-            <p class="text-gray-11 text-xs mb-4"> "Location: " (place) " · " (sub_count) " push subscriber(s)" </p>
+            <p class="text-gray-11 text-xs mb-4"> "Location: " (location.name)
+                @if place != location.name {
+                    " (" (place) ")"
+                }
+                " · " (sub_count) " push subscriber(s) · " <a href="/" class="text-gray-11">"All locations"</a> </p>
 
             <div class="grid grid-cols-[max-content_1fr_1fr_1fr_1fr] gap-x-2">
                 @for (idx, day) in day_groups.iter().enumerate() {
@@ -367,7 +484,7 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                     @let wind_s = if day.avg_wind.is_finite() { format!("{:.0}", day.avg_wind) } else { "-".into() };
                     @let price_s = if day.avg_price.is_finite() { format!("{:.1}", day.avg_price) } else { "-".into() };
                     @let is_today = day.date == today;
-                    @let day_label = if is_today { format!("Today") } else { day.label.clone() };
+                    @let day_label = if is_today { "Today".to_string() } else { day.label.clone() };
                     @let panel_id = format!("day-{idx}");
                     <div class="col-span-5 grid grid-cols-subgrid cursor-pointer py-2 px-3 mb-1 bg-gray-3 text-gray-12 text-sm font-medium select-none whitespace-nowrap"
                          onclick=(format!("document.getElementById('{panel_id}').toggleAttribute('hidden')"))>
@@ -382,7 +499,11 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                             <thead>
                                 <tr class="bg-gray-2">
                                     <th class="px-3 py-1.5 text-left font-medium text-gray-11">Time</th>
+                                    <th class="px-1 py-1.5"></th>
                                     <th class="px-3 py-1.5 text-left font-medium text-gray-11">Temp</th>
+                                    @if has_indoor {
+                                        <th class="px-3 py-1.5 text-left font-medium text-gray-11">Indoor</th>
+                                    }
                                     <th class="px-3 py-1.5 text-left font-medium text-gray-11">Wind</th>
                                     <th class="px-3 py-1.5 text-left font-medium text-gray-11">Precip</th>
                                     <th class="px-3 py-1.5 text-left font-medium text-gray-11">"E.Price"</th>
@@ -402,6 +523,11 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                                     } else {
                                         "-".to_string()
                                     };
+                                    @let gust = if row.wind_gust_ms.is_finite() {
+                                        format!(" ({:.0})", row.wind_gust_ms)
+                                    } else {
+                                        String::new()
+                                    };
                                     @let precip = if row.precipitation_mm.is_finite() {
                                         format!("{:.1}", row.precipitation_mm)
                                     } else {
@@ -412,15 +538,51 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                                         .get(&hour_ts)
                                         .map(|(sum, count)| format!("{:.1}", sum / *count as f64))
                                         .unwrap_or_else(|| "-".to_string());
+                                    @let spot = hourly_spot
+                                        .get(&hour_ts)
+                                        .filter(|_| components.is_configured())
+                                        .map(|(sum, count)| format!(" ({:.1})", sum / *count as f64))
+                                        .unwrap_or_default();
                                     @let current_hour_ts = now.timestamp() - (now.timestamp() % 3600);
                                     @let is_current = hour_ts == current_hour_ts;
                                     @let tr_class = if is_current { "bg-gray-4 font-bold" } else { "even:bg-gray-2" };
                                     <tr class=(tr_class)>
-                                        <td class="px-3 py-1.5"> (time_str) </td>
+                                        <td class="px-3 py-1.5 whitespace-nowrap">
+                                            (time_str)
+                                            @for change in radiator_changes.get(&hour_ts).into_iter().flatten() {
+                                                <span class="bg-red-a4 text-red-12 font-normal text-xs px-1 ms-1" title=(format!("{} set to {} ({})", change.radiator, change.setting, change.source))>
+                                                    @let label = heating::setting_label(change.setting);
+                                                    @if radiators.len() > 1 {
+                                                        @let name = radiators.iter().find(|r| r.radiator.id == change.radiator_id).map(|r| r.radiator.name.as_str()).unwrap_or(change.radiator.as_str());
+                                                        (format!("⚙ {name} {label}"))
+                                                    } @else {
+                                                        (format!("⚙ {label}"))
+                                                    }
+                                                </span>
+                                            }
+                                            @for (name, planned) in preheat_hours.get(&hour_ts).into_iter().flatten() {
+                                                <span class="bg-gray-a5 text-gray-12 font-normal text-xs px-1 ms-1" title=(format!("Preheat plan: {name} to {} at {:.1} snt", planned.setting, planned.price_cents_kwh))>
+                                                    @let label = format!("{} {}", planned.shift.arrow(), heating::setting_label(planned.setting));
+                                                    @if radiators.len() > 1 {
+                                                        (format!("{name} {label}"))
+                                                    } @else {
+                                                        (label)
+                                                    }
+                                                </span>
+                                            }
+                                        </td>
+                                        <td class="px-1 py-1.5"> (weather_icon(row.weather_symbol)) </td>
                                         <td class="px-3 py-1.5"> (format!("{}°C", temp)) </td>
-                                        <td class="px-3 py-1.5"> (format!("{} m/s", wind)) </td>
+                                        @if has_indoor {
+                                            @let indoor = indoor_hourly
+                                                .get(&hour_ts)
+                                                .map(|t| format!("{:.1}°C", t))
+                                                .unwrap_or_else(|| "-".to_string());
+                                            <td class="px-3 py-1.5 text-gray-11"> (indoor) </td>
+                                        }
+                                        <td class="px-3 py-1.5 whitespace-nowrap"> (format!("{} m/s", wind)) <span class="text-gray-11 font-normal"> (gust) </span> </td>
                                         <td class="px-3 py-1.5"> (format!("{} mm", precip)) </td>
-                                        <td class="px-3 py-1.5"> (format!("{} snt", price)) </td>
+                                        <td class="px-3 py-1.5 whitespace-nowrap"> (format!("{} snt", price)) <span class="text-gray-11 font-normal"> (spot) </span> </td>
                                     </tr>
                                 }
                             </tbody>
@@ -429,35 +591,61 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
                 }
             </div>
 
-            <form method="POST" action="/radiator" class="mt-8">
-                <h2 class="mb-2 text-gray-12 text-base">
-                    "Radiator Setting"
-
-                    @if recommended_setting.is_finite() {
-                        @let needs_adjust = current_radiator.map(|c| (c - recommended_setting).abs() >= 0.3).unwrap_or(false);
-                        @let rad_style = if needs_adjust { "text-white bg-red-a9 p-1 -m-1 ms-1 text-sm" } else { "ms-1.5 text-gray-11 text-sm" };
-                        @let rad_text = if needs_adjust { format!("adjust to → {:.1}", recommended_setting) } else { format!("ideal {:.1}", recommended_setting) };
-                        <span class=(rad_style)> (rad_text) </span>
-                    }
-                </h2>
-                <div class="flex gap-2 text-sm">
-                    @for (_, v) in [0.0_f64, 2.0, 3.5].iter().enumerate() {
-                        @let label = if *v == 0.0 { "Off" } else { &v.to_string() };
-                        @let base_classes = "focus flex-1 py-3 px-4 bg-gray-a4 text-gray-12 font-medium".to_owned();
-                        @let is_active_setting = current_radiator
-                            .map(|c| (c - v).abs() < 0.01)
-                            .unwrap_or(false);
-                        <button
-                            type="submit"
-                            name="radiator"
-                            value=(format!("{}", v))
-                            class=(base_classes + (if is_active_setting { " bg-gray-a8" } else { "" }))
-                        >
-                            (label)
-                        </button>
-                    }
-                </div>
-            </form>
+            @if is_home {
+                <h2 class="mt-8 mb-1 text-gray-12 text-base"> "Radiators" </h2>
+                @if weighted_avg.is_finite() {
+                    <p class="text-gray-11 text-xs mb-2">
+                        "Weighted 24h avg " (format!("{:.1}", weighted_avg)) "°C, effective "
+                        (format!("{:.1}", effective_avg)) "°C with wind and sun"
+                    </p>
+                }
+                @if !preheat_hours.is_empty() {
+                    <p class="text-gray-11 text-xs mb-2">
+                        "↑/↓ in the hourly table: preheat plan, raising in cheap hours to lower in expensive ones"
+                    </p>
+                }
+                @for row in &radiators {
+                    @let recommended_setting = row.recommended_setting;
+                    @let current_radiator = row.current_setting;
+                    <form method="POST" action="/radiator" class="mb-4">
+                        <input type="hidden" name="location" value=(location.slug)>
+                        <input type="hidden" name="radiator_id" value=(row.radiator.id.to_string())>
+                        <h3 class="mb-2 text-gray-12 text-sm font-medium">
+                            (row.radiator.name)
+
+                            @if recommended_setting.is_finite() {
+                                @let needs_adjust = current_radiator.map(|c| (c - recommended_setting).abs() >= 0.01).unwrap_or(false);
+                                @let rad_style = if needs_adjust { "text-white bg-red-a9 p-1 -m-1 ms-1 text-sm font-normal" } else { "ms-1.5 text-gray-11 text-sm font-normal" };
+                                @let rad_text = if needs_adjust { format!("adjust to → {:.1}", recommended_setting) } else { format!("ideal {:.1}", recommended_setting) };
+                                <span class=(rad_style)> (rad_text) </span>
+                            }
+                        </h3>
+                        <div class="flex gap-2 text-sm">
+                            @for v in radiator_settings.iter() {
+                                @let label = if *v == 0.0 { "Off" } else { &v.to_string() };
+                                @let base_classes = "focus flex-1 py-3 px-4 bg-gray-a4 text-gray-12 font-medium".to_owned();
+                                @let is_active_setting = current_radiator
+                                    .map(|c| (c - v).abs() < 0.01)
+                                    .unwrap_or(false);
+                                <button
+                                    type="submit"
+                                    name="radiator"
+                                    value=(format!("{}", v))
+                                    class=(base_classes + (if is_active_setting { " bg-gray-a8" } else { "" }))
+                                >
+                                    (label)
+                                </button>
+                            }
+                        </div>
+                    </form>
+                }
+            } @else if let Some(home) = &home {
+                <h2 class="mt-8 mb-1 text-gray-12 text-base"> "Radiators" </h2>
+                <p class="text-gray-11 text-xs mb-2">
+                    "Radiators follow the forecast for "
+                    <a href=(format!("/l/{}", home.slug)) class="text-gray-11"> (home.name) </a>
+                </p>
+            }
 
             <div class="flex gap-2 mt-8 flex-wrap">
                 <button id="push-btn" onclick="subscribePush()" class="bg-gray-a4 text-gray-12 px-4 py-2 border-none text-sm">Enable Push Notifications</button>
@@ -465,15 +653,38 @@ pub async fn handler(State(state): State<AppState>) -> Html<String> {
             </div>
             <div id="push-status" class="text-xs text-gray-11 mt-2"></div>
 
-            <div class="mt-8">
-                <a href="/" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">Refresh</a>
+            <div class="flex gap-2 mt-8 flex-wrap">
+                <a href=(format!("/l/{}", location.slug)) class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">Refresh</a>
+                <a href="/accuracy" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">"Forecast accuracy"</a>
+                <a href="/cheapest-window" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">"Cheapest window"</a>
+                <a href=(format!("/l/{}/degree-days", location.slug)) class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">"Degree days"</a>
+                <a href="/settings" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">"Settings"</a>
+                <a href="/api/radiator-history" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">"Radiator history"</a>
             </div>
         </body>
         </html>
-    }.render().into_inner())
+    }.render().into_inner()))
+}
+
+/// Icon for an FMI WeatherSymbol3 code.
+fn weather_icon(symbol: f64) -> &'static str {
+    if !symbol.is_finite() {
+        return "";
+    }
+    match symbol as i64 {
+        1 => "☀️",
+        2 => "⛅",
+        3 => "☁️",
+        21..=23 => "🌦️",
+        31..=33 => "🌧️",
+        41..=43 | 51..=53 | 71..=73 | 81..=83 => "🌨️",
+        61..=64 => "⛈️",
+        91 | 92 => "🌫️",
+        _ => "",
+    }
 }
 
-fn error_page(msg: &str) -> String {
+pub fn error_page(msg: &str) -> String {
     rsx! {
         <!DOCTYPE html>
         <html>
@@ -498,15 +709,25 @@ pub async fn radiator_handler(
     Form(form): Form<HashMap<String, String>>,
 ) -> Redirect {
     if let Some(val) = form.get("radiator").and_then(|v| v.parse::<f64>().ok()) {
-        // Only allow valid settings: 0 (off), 2.0, or 3.5
-        let val = if val >= 2.75 {
-            3.5
-        } else if val >= 1.0 {
-            2.0
-        } else {
-            0.0
-        };
-        let _ = state.db.set_radiator_setting(val).await;
+        // Only allow positions the heating curve actually uses
+        let curve = state.db.get_heating_curve().await.unwrap_or_default();
+        let source = form
+            .get("source")
+            .and_then(|s| SettingSource::parse(s))
+            .unwrap_or(SettingSource::Web);
+        let radiator_id = form
+            .get("radiator_id")
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(1);
+        if let Ok(Some(radiator)) = state.db.get_radiator(radiator_id).await {
+            let _ = state
+                .db
+                .set_radiator_setting(radiator.id, curve.snap(val), source)
+                .await;
+        }
+    }
+    match form.get("location") {
+        Some(slug) => Redirect::to(&format!("/l/{slug}")),
+        None => Redirect::to("/"),
     }
-    Redirect::to("/")
 }
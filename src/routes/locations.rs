@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Form, State},
+    response::{Html, Redirect},
+};
+use chrono::Utc;
+use hypertext::prelude::*;
+use serde::Deserialize;
+
+use crate::{config::WeatherProviderKind, db::NewLocation, routes::index::error_page, AppState};
+
+struct LocationSummary {
+    slug: String,
+    name: String,
+    current_temp: Option<f64>,
+}
+
+pub async fn handler(State(state): State<AppState>) -> Html<String> {
+    let locations = match state.db.list_locations().await {
+        Ok(l) => l,
+        Err(e) => return Html(error_page(&format!("Failed to load locations: {e}"))),
+    };
+
+    let now = Utc::now();
+    let obs_from = (now - chrono::Duration::hours(3))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let obs_to = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let mut summaries = Vec::with_capacity(locations.len());
+    for location in locations {
+        let current_temp = state
+            .db
+            .get_weather_observations(location.id, &obs_from, &obs_to)
+            .await
+            .unwrap_or_default()
+            .last()
+            .map(|o| o.temperature_c);
+        summaries.push(LocationSummary {
+            slug: location.slug,
+            name: location.name,
+            current_temp,
+        });
+    }
+
+    let default_tz = state.config.tz.name();
+
+    Html(rsx! {
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title> "Weather" </title>
+            <link rel="manifest" href="/manifest.json">
+            <meta name="theme-color" content="#000">
+            <link rel="stylesheet" href="/assets/styles.css">
+        </head>
+        <body class="bg-gray-1 text-gray-12 text-sm p-4 max-w-[37.5rem] mx-auto">
+            <h1 class="mb-2 text-gray-12 text-base"> "Locations" </h1>
+            <div class="flex flex-col gap-1">
+                @for loc in &summaries {
+                    @let temp_s = loc.current_temp.map(|t| format!("{:.1}°C", t)).unwrap_or_else(|| "-".into());
+                    <a href=(format!("/l/{}", loc.slug)) class="flex justify-between py-2 px-3 bg-gray-3 text-gray-12 font-medium no-underline">
+                        <span> (loc.name) </span>
+                        <span class="text-gray-11 font-normal"> (temp_s) </span>
+                    </a>
+                }
+            </div>
+
+            <form method="POST" action="/locations" class="mt-8 flex flex-col gap-2">
+                <h2 class="text-gray-12 text-base"> "Add location" </h2>
+                <input name="name" placeholder="Name" required class="focus2 bg-gray-a3 px-3 py-2">
+                <input name="place" placeholder="Place name, e.g. Kuopio" class="focus2 bg-gray-a3 px-3 py-2">
+                <input name="fmisid" placeholder="FMISID (optional)" class="focus2 bg-gray-a3 px-3 py-2">
+                <input name="fmisid_wind" placeholder="Wind FMISID (optional)" class="focus2 bg-gray-a3 px-3 py-2">
+                <input name="latlon" placeholder="lat,lon (optional)" class="focus2 bg-gray-a3 px-3 py-2">
+                <input name="timezone" value=(default_tz) required class="focus2 bg-gray-a3 px-3 py-2">
+                <button type="submit" class="focus py-3 px-4 bg-gray-a4 text-gray-12 font-medium"> "Add" </button>
+            </form>
+
+            <div class="flex gap-2 mt-8">
+                <a href="/settings" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">"Settings"</a>
+            </div>
+        </body>
+        </html>
+    }.render().into_inner())
+}
+
+#[derive(Deserialize)]
+pub struct AddLocationForm {
+    pub name: String,
+    pub fmisid: Option<String>,
+    pub fmisid_wind: Option<String>,
+    pub place: Option<String>,
+    pub latlon: Option<String>,
+    pub timezone: String,
+}
+
+pub async fn add(
+    State(state): State<AppState>,
+    Form(form): Form<AddLocationForm>,
+) -> Result<Redirect, Html<String>> {
+    let non_empty = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+
+    let timezone = form.timezone.trim();
+    if timezone.parse::<chrono_tz::Tz>().is_err() {
+        return Err(Html(error_page(&format!("Unknown timezone {timezone}"))));
+    }
+
+    let fmisid = non_empty(form.fmisid);
+    let place = non_empty(form.place);
+    let latlon = non_empty(form.latlon);
+    if fmisid.is_none() && place.is_none() && latlon.is_none() {
+        return Err(Html(error_page(
+            "A location needs a place name, FMISID or lat,lon",
+        )));
+    }
+    if state.config.weather_provider == WeatherProviderKind::OpenMeteo && latlon.is_none() {
+        return Err(Html(error_page("Open-Meteo locations need lat,lon")));
+    }
+
+    let location = NewLocation::new(
+        form.name.trim(),
+        fmisid,
+        non_empty(form.fmisid_wind),
+        place,
+        latlon,
+        timezone,
+    );
+    if let Err(e) = state.db.insert_location(&location).await {
+        return Err(Html(error_page(&format!("Failed to add location: {e}"))));
+    }
+
+    tracing::info!("Location added: {}", location.slug);
+    Ok(Redirect::to(&format!("/l/{}", location.slug)))
+}
@@ -1,2 +1,9 @@
+pub mod accuracy;
+pub mod cheapest_window;
+pub mod degree_days;
 pub mod index;
+pub mod locations;
 pub mod push;
+pub mod radiator;
+pub mod sensors;
+pub mod settings;
@@ -47,11 +47,7 @@ pub async fn subscribe(
         p256dh: body.p256dh,
         auth: body.auth,
     };
-    let vapid = VapidConfig {
-        subject: state.config.vapid_subject.clone(),
-        public_key_b64: state.config.vapid_public_key.clone(),
-        private_key_b64: state.config.vapid_private_key.clone(),
-    };
+    let vapid = VapidConfig::from_config(&state.config);
     let _ = notify::send_one_sub(&sub, "Notifications enabled!", &vapid).await;
 
     Json(ApiResponse {
@@ -81,19 +77,9 @@ pub async fn test_summary(State(state): State<AppState>) -> Json<ApiResponse> {
         }
     };
 
-    let vapid = VapidConfig {
-        subject: state.config.vapid_subject.clone(),
-        public_key_b64: state.config.vapid_public_key.clone(),
-        private_key_b64: state.config.vapid_private_key.clone(),
-    };
+    let vapid = VapidConfig::from_config(&state.config);
 
-    let results = notify::send_all(&subscriptions, &message, &vapid).await;
-    let success_count = results.iter().filter(|r| r.is_ok()).count();
-    tracing::info!(
-        "Test summary sent to {}/{} subscribers",
-        success_count,
-        subscriptions.len()
-    );
+    notify::send_all_logged(&subscriptions, &message, &vapid, "Test summary").await;
 
     Json(ApiResponse {
         ok: true,
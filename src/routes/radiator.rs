@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use chrono::Utc;
+use http::StatusCode;
+use serde::Deserialize;
+
+use crate::{db::RadiatorChange, AppState};
+
+const DEFAULT_DAYS: i64 = 30;
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    pub days: Option<i64>,
+}
+
+/// Every radiator setting change in the last `days` days, oldest first.
+pub async fn history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<RadiatorChange>>, (StatusCode, String)> {
+    let days = query.days.unwrap_or(DEFAULT_DAYS).max(1);
+    let now = Utc::now();
+    let from = (now - chrono::Duration::days(days))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let to = (now + chrono::Duration::minutes(1))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    state
+        .db
+        .get_radiator_history(&from, &to)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}")))
+}
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    Json as JsonBody,
+};
+use chrono::{DateTime, Utc};
+use http::{HeaderMap, StatusCode};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::{db::SensorReading, AppState};
+
+#[derive(Deserialize)]
+pub struct ReadingRequest {
+    pub temperature_c: f64,
+    pub humidity: Option<f64>,
+    /// RFC 3339; defaults to the time the reading arrives.
+    pub timestamp: Option<String>,
+    /// Slug of the location the sensor is in. Only needed on the first
+    /// reading or when moving a sensor; new sensors default to the first
+    /// location.
+    pub location: Option<String>,
+}
+
+/// A single reading or a batch of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ReadingsRequest {
+    One(ReadingRequest),
+    Many(Vec<ReadingRequest>),
+}
+
+#[derive(Serialize)]
+pub struct ReadingsResponse {
+    pub ok: bool,
+    pub stored: usize,
+}
+
+type ApiError = (StatusCode, String);
+
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(token) = &state.config.sensor_token else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Sensor ingestion is disabled, set SENSOR_TOKEN".into(),
+        ));
+    };
+    let provided = headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    // Constant-time, so response times don't reveal how much of a guess matched
+    let valid = provided.is_some_and(|p| bool::from(p.as_bytes().ct_eq(token.as_bytes())));
+    if !valid {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid sensor token".into()));
+    }
+    Ok(())
+}
+
+pub async fn post_readings(
+    State(state): State<AppState>,
+    Path(sensor_id): Path<String>,
+    headers: HeaderMap,
+    JsonBody(body): JsonBody<ReadingsRequest>,
+) -> Result<Json<ReadingsResponse>, ApiError> {
+    authorize(&state, &headers)?;
+    let internal = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}"));
+
+    let requests = match body {
+        ReadingsRequest::One(r) => vec![r],
+        ReadingsRequest::Many(rs) => rs,
+    };
+
+    let mut location_slug = None;
+    let mut readings = Vec::with_capacity(requests.len());
+    for r in requests {
+        if !r.temperature_c.is_finite() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "temperature_c must be a number".into(),
+            ));
+        }
+        let timestamp = match &r.timestamp {
+            Some(ts) => DateTime::parse_from_rfc3339(ts)
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid timestamp {ts}")))?
+                .to_utc(),
+            None => Utc::now(),
+        };
+        if r.location.is_some() {
+            location_slug = r.location;
+        }
+        readings.push(SensorReading {
+            timestamp: timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            temperature_c: r.temperature_c,
+            humidity: r.humidity.filter(|h| h.is_finite()),
+        });
+    }
+
+    let location_id = match location_slug {
+        Some(slug) => Some(
+            state
+                .db
+                .get_location(&slug)
+                .await
+                .map_err(internal)?
+                .ok_or((StatusCode::BAD_REQUEST, format!("Unknown location {slug}")))?
+                .id,
+        ),
+        None => None,
+    };
+    let known_location = state
+        .db
+        .get_sensor_location_id(&sensor_id)
+        .await
+        .map_err(internal)?;
+    match (location_id, known_location) {
+        (Some(id), known) if known != Some(id) => {
+            state
+                .db
+                .upsert_sensor(&sensor_id, id)
+                .await
+                .map_err(internal)?;
+            tracing::info!("Sensor {sensor_id} registered at location {id}");
+        }
+        (None, None) => {
+            let id = state.db.default_location().await.map_err(internal)?.id;
+            state
+                .db
+                .upsert_sensor(&sensor_id, id)
+                .await
+                .map_err(internal)?;
+            tracing::info!("Sensor {sensor_id} registered at location {id}");
+        }
+        _ => {}
+    }
+
+    state
+        .db
+        .insert_sensor_readings(&sensor_id, &readings)
+        .await
+        .map_err(internal)?;
+
+    Ok(Json(ReadingsResponse {
+        ok: true,
+        stored: readings.len(),
+    }))
+}
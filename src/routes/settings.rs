@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Form, Path, State},
+    response::{Html, Redirect},
+};
+use hypertext::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    appliances, cheapest_window, curve_tuning, db,
+    heating::{setting_label, CurveStep, HeatingCurve},
+    routes::index::error_page,
+    AppState,
+};
+
+/// Blank rows shown under the existing breakpoints for adding new ones.
+const EMPTY_ROWS: usize = 2;
+/// Target indoor temperature offered when `INDOOR_TARGET_C` isn't set.
+const DEFAULT_TARGET_C: f64 = 21.0;
+/// Days of data a manual proposal is fitted on by default.
+const PROPOSAL_DAYS: i64 = 30;
+/// Most days of data a proposal can be fitted on. A year covers every
+/// season, older data mostly reflects a different house or dial.
+const MAX_PROPOSAL_DAYS: i64 = 365;
+
+pub async fn handler(State(state): State<AppState>) -> Html<String> {
+    let curve = match state.db.get_heating_curve().await {
+        Ok(c) => c,
+        Err(e) => return Html(error_page(&format!("Failed to load heating curve: {e}"))),
+    };
+    let proposal = state
+        .db
+        .get_pending_curve_proposal()
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to load heating curve proposal: {e}");
+            None
+        });
+    let target_c = state.config.indoor_target_c.unwrap_or(DEFAULT_TARGET_C);
+    let describe = |curve: &HeatingCurve| -> Vec<String> {
+        curve
+            .steps
+            .iter()
+            .map(|s| format!("below {}°C → {}", s.below_c, setting_label(s.setting)))
+            .chain([format!("otherwise → {}", setting_label(curve.otherwise))])
+            .collect()
+    };
+    let current_lines = describe(&curve);
+    let radiators = state.db.list_radiators().await.unwrap_or_default();
+    let jobs = state.db.list_appliance_jobs().await.unwrap_or_default();
+    let now = chrono::Utc::now();
+    let runs: HashMap<i64, db::ApplianceRun> = state
+        .db
+        .list_appliance_runs(&now.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|run| (run.job_id, run))
+        .collect();
+    let tz = state.config.tz;
+    let next_run = |job: &db::ApplianceJob| -> String {
+        let local = |s: &str, format: &str| {
+            chrono::DateTime::parse_from_rfc3339(s)
+                .map(|dt| dt.with_timezone(&tz).format(format).to_string())
+                .unwrap_or_default()
+        };
+        match runs.get(&job.id) {
+            Some(run) => format!(
+                "Next: {}–{} at {:.2} snt/kWh, {:.1} snt",
+                local(&run.start, "%a %H:%M"),
+                local(&run.end, "%H:%M"),
+                run.avg_price_cents_kwh,
+                run.avg_price_cents_kwh * job.energy_kwh
+            ),
+            None => "Planned once prices up to the deadline are out".to_string(),
+        }
+    };
+
+    let rows: Vec<(String, String)> = curve
+        .steps
+        .iter()
+        .map(|s| (s.below_c.to_string(), s.setting.to_string()))
+        .chain(std::iter::repeat_with(Default::default).take(EMPTY_ROWS))
+        .collect();
+
+    Html(rsx! {
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title> "Weather – settings" </title>
+            <link rel="manifest" href="/manifest.json">
+            <meta name="theme-color" content="#000">
+            <link rel="stylesheet" href="/assets/styles.css">
+        </head>
+        <body class="bg-gray-1 text-gray-12 text-sm p-4 max-w-[37.5rem] mx-auto">
+            <h1 class="mb-1 text-gray-12 text-base"> "Heating curve" </h1>
+            <p class="text-gray-11 text-xs mb-4">
+                "The radiator setting for the weighted 24h forecast average. The coldest matching breakpoint wins; leave a row empty to remove it."
+            </p>
+
+            <form method="POST" action="/settings/heating-curve" class="flex flex-col gap-2">
+                <div class="grid grid-cols-[max-content_1fr_max-content_1fr] gap-2 items-center">
+                    @for (i, (below_c, setting)) in rows.iter().enumerate() {
+                        <span class="text-gray-11"> "Below" </span>
+                        <input name=(format!("below_{i}")) value=(below_c) placeholder="°C" inputmode="decimal" class="focus2 bg-gray-a3 px-3 py-2">
+                        <span class="text-gray-11"> "→" </span>
+                        <input name=(format!("setting_{i}")) value=(setting) placeholder="setting" inputmode="decimal" class="focus2 bg-gray-a3 px-3 py-2">
+                    }
+                    <span class="text-gray-11 col-span-3"> "Otherwise" </span>
+                    <input name="otherwise" value=(curve.otherwise.to_string()) required inputmode="decimal" class="focus2 bg-gray-a3 px-3 py-2">
+                </div>
+                <button type="submit" class="focus py-3 px-4 bg-gray-a4 text-gray-12 font-medium"> "Save" </button>
+            </form>
+
+            <p class="text-gray-11 text-xs mt-4">
+                "Dial positions: "
+                (curve.settings().into_iter().map(setting_label).collect::<Vec<_>>().join(", "))
+            </p>
+
+            <h2 class="mt-8 mb-1 text-gray-12 text-base"> "Radiators" </h2>
+            <p class="text-gray-11 text-xs mb-2">
+                "The offset is taken off the outdoor temperature before the curve is read: positive for rooms that run cold, negative for warm ones."
+            </p>
+            <div class="flex flex-col gap-2">
+                @for radiator in &radiators {
+                    <form method="POST" action=(format!("/settings/radiators/{}", radiator.id)) class="flex gap-2">
+                        <input name="name" value=(radiator.name) required class="focus2 bg-gray-a3 px-3 py-2 flex-1">
+                        <input name="curve_offset_c" value=(radiator.curve_offset_c.to_string()) required inputmode="decimal" title="Curve offset °C" class="focus2 bg-gray-a3 px-3 py-2 w-20">
+                        <button type="submit" class="focus py-2 px-4 bg-gray-a4 text-gray-12 font-medium"> "Save" </button>
+                    </form>
+                }
+                <form method="POST" action="/settings/radiators" class="flex gap-2">
+                    <input name="name" placeholder="New radiator, e.g. Bedroom" required class="focus2 bg-gray-a3 px-3 py-2 flex-1">
+                    <input name="curve_offset_c" value="0" required inputmode="decimal" title="Curve offset °C" class="focus2 bg-gray-a3 px-3 py-2 w-20">
+                    <button type="submit" class="focus py-2 px-4 bg-gray-a4 text-gray-12 font-medium"> "Add" </button>
+                </form>
+            </div>
+
+            <h2 class="mt-8 mb-1 text-gray-12 text-base"> "Appliances" </h2>
+            <p class="text-gray-11 text-xs mb-2">
+                "Each job runs once a day in the cheapest window that has it done by the deadline. A reminder is pushed "
+                (state.config.appliance_reminder_minutes) " minutes before the start."
+            </p>
+            <div class="flex flex-col gap-2">
+                @for job in &jobs {
+                    <div class="flex gap-2">
+                        <form method="POST" action=(format!("/settings/appliances/{}", job.id)) class="flex gap-2 flex-1">
+                            <input name="name" value=(job.name) required class="focus2 bg-gray-a3 px-3 py-2 flex-1 min-w-0">
+                            <input name="duration" value=(cheapest_window::format_duration(job.duration_minutes)) required title="Duration, e.g. 2h15m" class="focus2 bg-gray-a3 px-3 py-2 w-20">
+                            <input type="time" name="deadline" value=(job.deadline) required title="Done by" class="focus2 bg-gray-a3 px-3 py-2 w-24">
+                            <input name="energy_kwh" value=(job.energy_kwh.to_string()) required inputmode="decimal" title="Energy kWh" class="focus2 bg-gray-a3 px-3 py-2 w-16">
+                            <button type="submit" class="focus py-2 px-4 bg-gray-a4 text-gray-12 font-medium"> "Save" </button>
+                        </form>
+                        <form method="POST" action=(format!("/settings/appliances/{}/delete", job.id))>
+                            <button type="submit" class="focus py-2 px-4 bg-gray-a4 text-gray-12 font-medium"> "Delete" </button>
+                        </form>
+                    </div>
+                    <p class="text-gray-11 text-xs"> (next_run(job)) </p>
+                }
+                <form method="POST" action="/settings/appliances" class="flex gap-2">
+                    <input name="name" placeholder="New job, e.g. Dishwasher" required class="focus2 bg-gray-a3 px-3 py-2 flex-1 min-w-0">
+                    <input name="duration" placeholder="2h15m" required title="Duration, e.g. 2h15m" class="focus2 bg-gray-a3 px-3 py-2 w-20">
+                    <input type="time" name="deadline" value="07:00" required title="Done by" class="focus2 bg-gray-a3 px-3 py-2 w-24">
+                    <input name="energy_kwh" placeholder="kWh" required inputmode="decimal" title="Energy kWh" class="focus2 bg-gray-a3 px-3 py-2 w-16">
+                    <button type="submit" class="focus py-2 px-4 bg-gray-a4 text-gray-12 font-medium"> "Add" </button>
+                </form>
+            </div>
+
+            <h2 class="mt-8 mb-1 text-gray-12 text-base"> "Learn from indoor temperature" </h2>
+            <p class="text-gray-11 text-xs mb-2">
+                "Fits how indoor temperature follows the outdoor temperature and the dial, and proposes breakpoints that hold the target. Nothing changes until you approve."
+            </p>
+            @if let Some(p) = &proposal {
+                <div class="bg-gray-2 p-3 mb-2">
+                    <p class="mb-2">
+                        "Proposal from " (p.created_at) " for " (format!("{:.1}", p.target_c)) "°C indoors"
+                    </p>
+                    <p class="text-gray-11 text-xs mb-2">
+                        (format!(
+                            "indoor ≈ {:.1} + {:.2}·outdoor + {:.2}·setting · {} hours · R² {:.2}",
+                            p.model.intercept, p.model.outdoor_coef, p.model.setting_coef, p.model.samples, p.model.r_squared
+                        ))
+                    </p>
+                    <div class="grid grid-cols-2 gap-2 mb-2">
+                        <div>
+                            <p class="text-gray-11 text-xs"> "Current" </p>
+                            @for line in &current_lines {
+                                <p> (line) </p>
+                            }
+                        </div>
+                        <div>
+                            <p class="text-gray-11 text-xs"> "Proposed" </p>
+                            @for line in describe(&p.curve) {
+                                <p> (line) </p>
+                            }
+                        </div>
+                    </div>
+                    <div class="flex gap-2">
+                        <form method="POST" action=(format!("/settings/heating-curve/proposals/{}/approve", p.id)) class="flex-1">
+                            <button type="submit" class="focus w-full py-3 px-4 bg-gray-a4 text-gray-12 font-medium"> "Approve" </button>
+                        </form>
+                        <form method="POST" action=(format!("/settings/heating-curve/proposals/{}/reject", p.id)) class="flex-1">
+                            <button type="submit" class="focus w-full py-3 px-4 bg-gray-a4 text-gray-12 font-medium"> "Reject" </button>
+                        </form>
+                    </div>
+                </div>
+            }
+            <form method="POST" action="/settings/heating-curve/propose" class="flex gap-2">
+                <input name="target_c" value=(target_c.to_string()) required inputmode="decimal" title="Target indoor °C" class="focus2 bg-gray-a3 px-3 py-2 flex-1">
+                <input name="days" value=(PROPOSAL_DAYS.to_string()) required inputmode="numeric" title="Days of data" class="focus2 bg-gray-a3 px-3 py-2 flex-1">
+                <button type="submit" class="focus py-2 px-4 bg-gray-a4 text-gray-12 font-medium"> "Propose" </button>
+            </form>
+
+            <div class="flex gap-2 mt-8">
+                <a href="/" class="bg-gray-a4 text-gray-12 px-4 py-2 text-sm inline-block no-underline">Back</a>
+            </div>
+        </body>
+        </html>
+    }.render().into_inner())
+}
+
+fn parse_curve(form: &HashMap<String, String>) -> anyhow::Result<HeatingCurve> {
+    let num = |key: &str| -> anyhow::Result<Option<f64>> {
+        match form.get(key).map(|v| v.trim().replace(',', ".")) {
+            Some(v) if !v.is_empty() => v
+                .parse::<f64>()
+                .map(Some)
+                .map_err(|_| anyhow::anyhow!("{v} is not a number")),
+            _ => Ok(None),
+        }
+    };
+
+    let mut steps = Vec::new();
+    let mut i = 0;
+    while form.contains_key(&format!("below_{i}")) {
+        match (num(&format!("below_{i}"))?, num(&format!("setting_{i}"))?) {
+            (Some(below_c), Some(setting)) => steps.push(CurveStep { below_c, setting }),
+            (None, None) => {}
+            _ => anyhow::bail!("Each breakpoint needs both a temperature and a setting"),
+        }
+        i += 1;
+    }
+
+    let otherwise = num("otherwise")?.unwrap_or(0.0);
+    HeatingCurve::new(steps, otherwise)
+}
+
+pub async fn save_heating_curve(
+    State(state): State<AppState>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Redirect, Html<String>> {
+    let curve =
+        parse_curve(&form).map_err(|e| Html(error_page(&format!("Invalid heating curve: {e}"))))?;
+    if let Err(e) = state.db.set_heating_curve(&curve).await {
+        return Err(Html(error_page(&format!(
+            "Failed to save heating curve: {e}"
+        ))));
+    }
+    tracing::info!("Heating curve updated: {curve:?}");
+    Ok(Redirect::to("/settings"))
+}
+
+#[derive(Deserialize)]
+pub struct ProposeForm {
+    pub target_c: f64,
+    pub days: i64,
+}
+
+pub async fn propose_heating_curve(
+    State(state): State<AppState>,
+    Form(form): Form<ProposeForm>,
+) -> Result<Redirect, Html<String>> {
+    let location = state
+        .db
+        .default_location()
+        .await
+        .map_err(|e| Html(error_page(&format!("Failed to load location: {e}"))))?;
+    if form.days > MAX_PROPOSAL_DAYS {
+        return Err(Html(error_page(&format!(
+            "A proposal can be fitted on at most {MAX_PROPOSAL_DAYS} days of data"
+        ))));
+    }
+    match curve_tuning::create_proposal(
+        &state.db,
+        &location,
+        form.target_c,
+        form.days.max(1),
+        false,
+    )
+    .await
+    {
+        Ok(Some(_)) => Ok(Redirect::to("/settings")),
+        Ok(None) => Err(Html(error_page(
+            "The fitted curve is the same as the current one, nothing to propose",
+        ))),
+        Err(e) => Err(Html(error_page(&format!(
+            "Can't propose a heating curve: {e}"
+        )))),
+    }
+}
+
+pub async fn approve_proposal(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Redirect, Html<String>> {
+    let proposal = match state.db.resolve_curve_proposal(id, "approved").await {
+        Ok(Some(p)) => p,
+        Ok(None) => return Err(Html(error_page("That proposal is no longer pending"))),
+        Err(e) => {
+            return Err(Html(error_page(&format!(
+                "Failed to approve proposal: {e}"
+            ))))
+        }
+    };
+    // Re-validate rather than trusting what was stored
+    let curve = HeatingCurve::new(proposal.curve.steps, proposal.curve.otherwise)
+        .map_err(|e| Html(error_page(&format!("Invalid heating curve: {e}"))))?;
+    if let Err(e) = state.db.set_heating_curve(&curve).await {
+        return Err(Html(error_page(&format!(
+            "Failed to save heating curve: {e}"
+        ))));
+    }
+    tracing::info!("Heating curve proposal {id} approved: {curve:?}");
+    Ok(Redirect::to("/settings"))
+}
+
+pub async fn reject_proposal(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Redirect, Html<String>> {
+    if let Err(e) = state.db.resolve_curve_proposal(id, "rejected").await {
+        return Err(Html(error_page(&format!("Failed to reject proposal: {e}"))));
+    }
+    tracing::info!("Heating curve proposal {id} rejected");
+    Ok(Redirect::to("/settings"))
+}
+
+#[derive(Deserialize)]
+pub struct RadiatorForm {
+    pub name: String,
+    pub curve_offset_c: f64,
+}
+
+fn validate_radiator(form: &RadiatorForm) -> Result<&str, Html<String>> {
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Err(Html(error_page("A radiator needs a name")));
+    }
+    if !form.curve_offset_c.is_finite() {
+        return Err(Html(error_page("The curve offset must be a number")));
+    }
+    Ok(name)
+}
+
+pub async fn add_radiator(
+    State(state): State<AppState>,
+    Form(form): Form<RadiatorForm>,
+) -> Result<Redirect, Html<String>> {
+    let name = validate_radiator(&form)?;
+    if let Err(e) = state.db.insert_radiator(name, form.curve_offset_c).await {
+        return Err(Html(error_page(&format!("Failed to add radiator: {e}"))));
+    }
+    tracing::info!("Radiator added: {name}");
+    Ok(Redirect::to("/settings"))
+}
+
+pub async fn update_radiator(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Form(form): Form<RadiatorForm>,
+) -> Result<Redirect, Html<String>> {
+    let name = validate_radiator(&form)?;
+    if let Err(e) = state
+        .db
+        .update_radiator(id, name, form.curve_offset_c)
+        .await
+    {
+        return Err(Html(error_page(&format!("Failed to update radiator: {e}"))));
+    }
+    tracing::info!(
+        "Radiator {id} updated: {name}, offset {}",
+        form.curve_offset_c
+    );
+    Ok(Redirect::to("/settings"))
+}
+
+#[derive(Deserialize)]
+pub struct ApplianceForm {
+    pub name: String,
+    pub duration: String,
+    pub deadline: String,
+    pub energy_kwh: f64,
+}
+
+fn validate_appliance(form: &ApplianceForm) -> Result<db::NewApplianceJob, Html<String>> {
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Err(Html(error_page("An appliance job needs a name")));
+    }
+    let Some(duration) = cheapest_window::parse_duration(&form.duration) else {
+        return Err(Html(error_page(
+            "The duration must look like 2h15m, 6h or 45m, at most 24 hours",
+        )));
+    };
+    let Some(deadline) = appliances::parse_deadline(&form.deadline) else {
+        return Err(Html(error_page("The deadline must be a time like 07:00")));
+    };
+    if duration > chrono::Duration::hours(24) {
+        return Err(Html(error_page("A job can run for at most 24 hours")));
+    }
+    if !form.energy_kwh.is_finite() || form.energy_kwh < 0.0 {
+        return Err(Html(error_page("The energy must be a number of kWh")));
+    }
+    Ok(db::NewApplianceJob {
+        name: name.to_string(),
+        duration_minutes: duration.num_minutes(),
+        deadline: deadline.format("%H:%M").to_string(),
+        energy_kwh: form.energy_kwh,
+    })
+}
+
+pub async fn add_appliance(
+    State(state): State<AppState>,
+    Form(form): Form<ApplianceForm>,
+) -> Result<Redirect, Html<String>> {
+    let job = validate_appliance(&form)?;
+    if let Err(e) = state.db.insert_appliance_job(&job).await {
+        return Err(Html(error_page(&format!(
+            "Failed to add appliance job: {e}"
+        ))));
+    }
+    tracing::info!("Appliance job added: {}", job.name);
+    Ok(Redirect::to("/settings"))
+}
+
+pub async fn update_appliance(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Form(form): Form<ApplianceForm>,
+) -> Result<Redirect, Html<String>> {
+    let job = validate_appliance(&form)?;
+    if let Err(e) = state.db.update_appliance_job(id, &job).await {
+        return Err(Html(error_page(&format!(
+            "Failed to update appliance job: {e}"
+        ))));
+    }
+    tracing::info!(
+        "Appliance job {id} updated: {}, {} min by {}, {} kWh",
+        job.name,
+        job.duration_minutes,
+        job.deadline,
+        job.energy_kwh
+    );
+    Ok(Redirect::to("/settings"))
+}
+
+pub async fn delete_appliance(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Redirect, Html<String>> {
+    if let Err(e) = state.db.delete_appliance_job(id).await {
+        return Err(Html(error_page(&format!(
+            "Failed to delete appliance job: {e}"
+        ))));
+    }
+    tracing::info!("Appliance job {id} deleted");
+    Ok(Redirect::to("/settings"))
+}
@@ -1,27 +1,78 @@
-use chrono::{TimeZone, Timelike, Utc};
+use chrono::{NaiveDate, TimeZone, Timelike, Utc};
 use std::collections::HashMap;
 use tracing::{error, info};
 
 use crate::{
+    appliances, backfill, cheapest_window,
     config::Config,
-    db, electricity, notify,
-    notify::VapidConfig,
-    weather::{self, temp_to_radiator_setting, ForecastPoint},
+    curve_tuning, db,
+    electricity::{self, PriceProvider},
+    heating, mqtt,
+    notify::{self, VapidConfig},
+    preheat::{self, Shift},
+    price_alerts,
+    weather::{ForecastPoint, ForecastRun, Provider, WeatherProvider},
 };
 
+/// Days of indoor readings the daily heating curve proposal is fitted on.
+const CURVE_LEARNING_DAYS: i64 = 30;
+/// Most hours of observations fetched per catch-up, so a long outage is
+/// filled in over several runs.
+const CATCH_UP_MAX_HOURS: i64 = 7 * 24;
+/// Days back gaps in the stored observations are filled in.
+const GAP_LOOKBACK_DAYS: i64 = 7;
+/// Local hours (inclusive start, exclusive end) tomorrow's prices are polled
+/// for every `PRICE_POLL_MINUTES` until they're in. Day-ahead prices are
+/// published in the early afternoon.
+const PRICE_PUBLICATION_HOURS: (u32, u32) = (13, 18);
+const PRICE_POLL_MINUTES: i64 = 10;
+/// Length of the cheapest and most expensive blocks in the overview of
+/// tomorrow's prices.
+const PRICE_BLOCK_HOURS: i64 = 3;
+
 pub fn spawn(db: db::Db, config: Config) {
+    let mqtt = config.mqtt.clone().map(|c| mqtt::spawn(db.clone(), c));
+
+    // Appliance runs start on any quarter hour, so their reminders are
+    // checked every minute instead of with the hourly run.
+    let appliance_db = db.clone();
+    let appliance_config = config.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = appliances::run_check(&appliance_db, &appliance_config).await {
+                error!("Appliance reminder error: {e}");
+            }
+        }
+    });
+
     tokio::spawn(async move {
         loop {
             if let Err(e) = run_check(&db, &config).await {
                 error!("Scheduler error: {e}");
             }
+            if let Some(mqtt) = &mqtt {
+                if let Err(e) = publish_mqtt(&db, &config, mqtt).await {
+                    error!("Failed to publish MQTT state: {e}");
+                }
+            }
             let now = Utc::now().with_timezone(&config.tz);
             let next_hour = (now + chrono::Duration::hours(1))
                 .with_minute(2)
                 .unwrap()
                 .with_second(0)
                 .unwrap();
-            let sleep_duration = (next_hour - now)
+
+            // Poll more often while tomorrow's prices are due
+            let poll = chrono::Duration::minutes(PRICE_POLL_MINUTES);
+            while Utc::now() + poll < next_hour && awaiting_tomorrow_prices(&db, &config).await {
+                info!("Tomorrow's prices not in yet, checking again in {PRICE_POLL_MINUTES} min");
+                tokio::time::sleep(poll.to_std().unwrap()).await;
+                fetch_and_store_prices(&db, &config).await;
+            }
+
+            let sleep_duration = (next_hour.with_timezone(&Utc) - Utc::now())
                 .to_std()
                 .unwrap_or(std::time::Duration::from_secs(3600));
             info!(
@@ -33,11 +84,221 @@ pub fn spawn(db: db::Db, config: Config) {
     });
 }
 
+/// Load the latest stored forecast for `location` from the current hour onwards.
+/// Falls back to a live fetch (which is then stored) when no snapshot exists yet.
+pub async fn load_forecast(
+    db: &db::Db,
+    config: &Config,
+    location: &db::Location,
+) -> anyhow::Result<Vec<ForecastPoint>> {
+    let now = Utc::now();
+    let hour_ts = now.timestamp() - (now.timestamp() % 3600);
+    let from = chrono::DateTime::from_timestamp(hour_ts, 0)
+        .unwrap()
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+
+    if let Some(run) = db.get_latest_forecast_snapshot(location.id, &from).await? {
+        if !run.points.is_empty() {
+            return Ok(run.points);
+        }
+    }
+
+    let site = location.site(config.weather_provider);
+    info!(
+        "No stored forecast snapshot for {}, fetching live for {site}",
+        location.slug
+    );
+    let run = Provider::from_config(config).fetch_forecast(site).await?;
+    store_resolved_name(db, location, &run).await;
+    if let Err(e) = db.insert_forecast_snapshot(location.id, &run).await {
+        error!("Failed to store forecast snapshot: {e}");
+    }
+    Ok(run.points)
+}
+
+/// Recommend a setting for `radiator` at the weighted forecast average
+/// `temp_c`, shifted by the radiator's curve offset, applying the configured
+/// deadband and minimum dwell to its stored setting.
+pub async fn radiator_recommendation(
+    db: &db::Db,
+    config: &Config,
+    radiator: &db::Radiator,
+    temp_c: f64,
+) -> anyhow::Result<heating::Recommendation> {
+    let curve = db.get_heating_curve().await?;
+    let current = db
+        .get_radiator_state(radiator.id)
+        .await?
+        .map(|state| heating::CurrentSetting {
+            setting: state.setting,
+            since: chrono::DateTime::parse_from_rfc3339(&state.updated_at)
+                .map(|dt| dt.to_utc())
+                .unwrap_or_else(|_| Utc::now()),
+        });
+    Ok(heating::recommend(
+        &curve,
+        temp_c - radiator.curve_offset_c,
+        current,
+        config.heating_deadband_c,
+        chrono::Duration::hours(config.radiator_min_dwell_hours),
+        Utc::now(),
+    ))
+}
+
+async fn store_resolved_name(db: &db::Db, location: &db::Location, run: &ForecastRun) {
+    let Some(name) = &run.place_name else {
+        return;
+    };
+    if location.resolved_name.as_ref() != Some(name) {
+        info!("Location {} resolved to {name}", location.slug);
+        if let Err(e) = db.set_location_resolved_name(location.id, name).await {
+            error!("Failed to store resolved name for {}: {e}", location.slug);
+        }
+    }
+}
+
+/// Fetch observations for `location` since the last stored one (or the last
+/// day, if there are none) and store them, catching up after any downtime
+/// at most `CATCH_UP_MAX_HOURS` at a time.
+pub async fn refresh_observations(
+    db: &db::Db,
+    config: &Config,
+    location: &db::Location,
+) -> anyhow::Result<()> {
+    let from = backfill::default_start(db, location).await?;
+    let to = Utc::now().min(from + chrono::Duration::hours(CATCH_UP_MAX_HOURS));
+    backfill::backfill_observations(db, config, location, from, to).await?;
+    Ok(())
+}
+
+/// Fill in the oldest gap in `location`'s observations over the last
+/// `GAP_LOOKBACK_DAYS`, at most `CATCH_UP_MAX_HOURS` of it. Each gap is tried
+/// once a day, so hours the station never reported aren't fetched hourly.
+async fn fill_observation_gap(
+    db: &db::Db,
+    config: &Config,
+    location: &db::Location,
+) -> anyhow::Result<()> {
+    let now = Utc::now();
+    let hour = |dt: chrono::DateTime<Utc>| dt.timestamp() - dt.timestamp().rem_euclid(3600);
+    let from = hour(now - chrono::Duration::days(GAP_LOOKBACK_DAYS));
+    // The latest hours may not be published yet
+    let to = hour(now - chrono::Duration::hours(2));
+    let format = |ts: i64| {
+        chrono::DateTime::from_timestamp(ts, 0)
+            .unwrap()
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string()
+    };
+    let stored: std::collections::HashSet<i64> = db
+        .get_weather_observations(location.id, &format(from), &format(to))
+        .await?
+        .iter()
+        .filter_map(|o| chrono::DateTime::parse_from_rfc3339(&o.timestamp).ok())
+        .map(|dt| dt.timestamp())
+        .collect();
+    let Some(gap_start) = (from..to).step_by(3600).find(|ts| !stored.contains(ts)) else {
+        return Ok(());
+    };
+    let gap_end = (gap_start..to)
+        .step_by(3600)
+        .find(|ts| stored.contains(ts))
+        .unwrap_or(to)
+        .min(gap_start + CATCH_UP_MAX_HOURS * 3600);
+
+    let key = format!("observation_gap_{}_{}", location.slug, format(gap_start));
+    let today = now.date_naive();
+    if db.already_notified(&key, today).await? {
+        return Ok(());
+    }
+    db.log_notification(&key, today).await?;
+    info!(
+        "Filling {} observation gap {} .. {}",
+        location.slug,
+        format(gap_start),
+        format(gap_end)
+    );
+    let (Some(gap_start), Some(gap_end)) = (
+        chrono::DateTime::from_timestamp(gap_start, 0),
+        chrono::DateTime::from_timestamp(gap_end, 0),
+    ) else {
+        return Ok(());
+    };
+    backfill::backfill_observations(db, config, location, gap_start, gap_end).await?;
+    Ok(())
+}
+
+/// Catch up on observations after downtime, or fill an older gap when the
+/// latest ones are current.
+async fn catch_up_observations(db: &db::Db, config: &Config, location: &db::Location) {
+    let obs_stale = match db.get_latest_observation_timestamp(location.id).await {
+        Ok(Some(latest)) => match chrono::DateTime::parse_from_rfc3339(&latest) {
+            Ok(latest_dt) => {
+                let hours_ago = (Utc::now() - latest_dt.to_utc()).num_hours();
+                info!(
+                    "Latest weather observation for {} is {hours_ago}h old",
+                    location.slug
+                );
+                hours_ago >= 2
+            }
+            Err(_) => true,
+        },
+        _ => true,
+    };
+    let result = if obs_stale {
+        refresh_observations(db, config, location).await
+    } else {
+        fill_observation_gap(db, config, location).await
+    };
+    if let Err(e) = result {
+        error!(
+            "Failed to refresh weather observations for {}: {e}",
+            location.slug
+        );
+    }
+}
+
+async fn refresh_location(db: &db::Db, config: &Config, location: &db::Location) {
+    // Weather observations are caught up in the background, so a long
+    // backfill doesn't hold up the notifications below
+    let (catch_up_db, catch_up_config, catch_up_location) =
+        (db.clone(), config.clone(), location.clone());
+    tokio::spawn(async move {
+        catch_up_observations(&catch_up_db, &catch_up_config, &catch_up_location).await;
+    });
+
+    let site = location.site(config.weather_provider);
+    info!(
+        "Scheduler: fetching forecast for {} ({site})",
+        location.slug
+    );
+
+    match Provider::from_config(config).fetch_forecast(site).await {
+        Ok(run) => {
+            store_resolved_name(db, location, &run).await;
+            match db.insert_forecast_snapshot(location.id, &run).await {
+                Ok(true) => info!(
+                    "Stored forecast snapshot issued at {} ({} points)",
+                    run.issued_at,
+                    run.points.len()
+                ),
+                Ok(false) => info!("Forecast issued at {} already stored", run.issued_at),
+                Err(e) => error!("Failed to store forecast snapshot: {e}"),
+            }
+        }
+        Err(e) => {
+            error!("Failed to fetch forecast for {}: {e}", location.slug);
+        }
+    }
+}
+
 pub async fn build_daily_summary(db: &db::Db, config: &Config) -> anyhow::Result<String> {
-    let forecast = weather::fetch_forecast(&config.fmi_sid).await?;
+    let location = db.default_location().await?;
+    let forecast = load_forecast(db, config, &location).await?;
 
     let now = Utc::now();
-    let tz = config.tz;
+    let tz = location.tz();
 
     let next_24h: Vec<_> = forecast
         .iter()
@@ -56,11 +317,13 @@ pub async fn build_daily_summary(db: &db::Db, config: &Config) -> anyhow::Result
         .filter(|t| t.is_finite())
         .fold(f64::NEG_INFINITY, f64::max);
 
-    let weighted_avg = ForecastPoint::weighted_avg_temperature(&forecast, 0.9, 24, 3);
-    let recommended_setting = temp_to_radiator_setting(weighted_avg);
+    let effective_avg = config
+        .temperature_adjustment()
+        .weighted_avg(&forecast, tz, 0.9, 24, 3);
 
     let temp_at = |local_hour: u32| -> String {
-        let target = now.with_timezone(&tz)
+        let target = now
+            .with_timezone(&tz)
             .date_naive()
             .and_hms_opt(local_hour, 0, 0)
             .unwrap();
@@ -88,7 +351,11 @@ pub async fn build_daily_summary(db: &db::Db, config: &Config) -> anyhow::Result
         "?".to_string()
     };
 
-    let today_start = now.with_timezone(&tz).date_naive().and_hms_opt(0, 0, 0).unwrap();
+    let today_start = now
+        .with_timezone(&tz)
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
     let today_start_utc = tz.from_local_datetime(&today_start).unwrap().to_utc();
     let today_end_utc = today_start_utc + chrono::Duration::hours(24);
     let price_from = today_start_utc.format("%Y-%m-%dT%H:%M:%SZ").to_string();
@@ -98,15 +365,25 @@ pub async fn build_daily_summary(db: &db::Db, config: &Config) -> anyhow::Result
         .await
         .unwrap_or_default();
 
+    // Ranked on the total price: under a time-of-use transfer tariff the
+    // cheapest spot hour isn't always the cheapest hour
+    let components = &config.price_components;
     let mut hourly: HashMap<i64, (f64, usize)> = HashMap::new();
+    let mut spot_sum = 0.0;
     for p in &prices {
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&p.timestamp) {
             let h = dt.to_utc().timestamp() / 3600 * 3600;
             let e = hourly.entry(h).or_insert((0.0, 0));
-            e.0 += p.price_cents_kwh;
+            e.0 += components.total(p.price_cents_kwh, dt.to_utc(), tz);
             e.1 += 1;
+            spot_sum += p.price_cents_kwh;
         }
     }
+    let spot_part = if components.is_configured() && !prices.is_empty() {
+        format!(" (spot {:.1})", spot_sum / prices.len() as f64)
+    } else {
+        String::new()
+    };
 
     let avg_price = if !hourly.is_empty() {
         let total: f64 = hourly.values().map(|(s, c)| s / *c as f64).sum::<f64>();
@@ -146,7 +423,11 @@ pub async fn build_daily_summary(db: &db::Db, config: &Config) -> anyhow::Result
         });
 
     // Daytime (9–21) wind and precipitation averages
-    let day_start = now.with_timezone(&tz).date_naive().and_hms_opt(9, 0, 0).unwrap();
+    let day_start = now
+        .with_timezone(&tz)
+        .date_naive()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
     let day_start_utc = tz.from_local_datetime(&day_start).unwrap().to_utc();
     let day_end_utc = day_start_utc + chrono::Duration::hours(12);
     let daytime: Vec<_> = forecast
@@ -187,118 +468,443 @@ pub async fn build_daily_summary(db: &db::Db, config: &Config) -> anyhow::Result
     let price_part = match (avg_price, &cheapest, &most_expensive) {
         (Some(avg), Some((cheap, cheap_t)), Some((exp, exp_t))) => {
             format!(
-                "\nE: avg {:.1} | {:.1}@{}..{:.1}@{} snt",
+                "\nE: avg {:.1}{spot_part} | {:.1}@{}..{:.1}@{} snt",
                 avg, cheap, cheap_t, exp, exp_t
             )
         }
-        (Some(avg), _, _) => format!("\nE: avg {:.1} snt", avg),
+        (Some(avg), _, _) => format!("\nE: avg {:.1}{spot_part} snt", avg),
         _ => String::new(),
     };
 
-    let current_setting = db.get_radiator_setting().await.ok().flatten();
-    let radiator_part = if recommended_setting.is_finite() {
+    let mut radiator_changes = Vec::new();
+    for radiator in db.list_radiators().await? {
+        let recommended_setting = radiator_recommendation(db, config, &radiator, effective_avg)
+            .await?
+            .setting;
+        if !recommended_setting.is_finite() {
+            continue;
+        }
+        let current_setting = db.get_radiator_setting(radiator.id).await.ok().flatten();
         let already_set = current_setting
             .map(|c| (c - recommended_setting).abs() < 0.01)
             .unwrap_or(false);
-        if already_set {
-            String::new()
-        } else {
-            let label = if recommended_setting == 0.0 {
-                "off"
-            } else if recommended_setting == 3.5 {
-                "3.5"
-            } else {
-                "2"
-            };
-            format!(" | Radiator → {}", label)
+        if !already_set {
+            radiator_changes.push(format!(
+                "{} → {}",
+                radiator.name,
+                heating::setting_label(recommended_setting)
+            ));
         }
-    } else {
+    }
+    let radiator_part = if radiator_changes.is_empty() {
         String::new()
+    } else {
+        format!(" | {}", radiator_changes.join(", "))
+    };
+
+    let indoor_since = (now - chrono::Duration::hours(2))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let indoor_part = match db
+        .get_current_indoor_temperature(location.id, &indoor_since)
+        .await
+    {
+        Ok(Some(t)) => format!("\nI: {:.1}°C", t),
+        Ok(None) => String::new(),
+        Err(e) => {
+            error!("Failed to load indoor temperature: {e}");
+            String::new()
+        }
     };
 
     Ok(format!(
-        "W: {}..{} | {}..{}{wind_part}{precip_part}{}{radiator_part}",
-        min_str, max_str, temp_9, temp_16, price_part
+        "{}\nW: {}..{} | {}..{}{wind_part}{precip_part}{indoor_part}{}{radiator_part}",
+        location.place_label(),
+        min_str,
+        max_str,
+        temp_9,
+        temp_16,
+        price_part
     ))
 }
 
-async fn run_check(db: &db::Db, config: &Config) -> anyhow::Result<()> {
-    let needs_fetch = match db.get_latest_electricity_timestamp().await {
-        Ok(Some(latest)) => match chrono::DateTime::parse_from_rfc3339(&latest) {
-            Ok(latest_dt) => {
-                let hours_ahead = (latest_dt.to_utc() - Utc::now()).num_hours();
-                info!("Latest electricity price is {hours_ahead}h ahead");
-                hours_ahead < 12
-            }
-            Err(_) => true,
+/// Publish the default location's current temperature, next-24h range,
+/// current electricity price and radiator recommendations.
+async fn publish_mqtt(db: &db::Db, config: &Config, mqtt: &mqtt::Mqtt) -> anyhow::Result<()> {
+    let location = db.default_location().await?;
+    let forecast = load_forecast(db, config, &location).await?;
+    let now = Utc::now();
+    let tz = location.tz();
+
+    let next_24h: Vec<f64> = forecast
+        .iter()
+        .filter(|p| p.timestamp >= now && p.timestamp <= now + chrono::Duration::hours(24))
+        .map(|p| p.temperature_c)
+        .filter(|t| t.is_finite())
+        .collect();
+    let min_temp_c = next_24h.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_temp_c = next_24h.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    // Latest observation from the last couple of hours, else the forecast
+    let obs_from = (now - chrono::Duration::hours(2))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let obs_to = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let observed = db
+        .get_weather_observations(location.id, &obs_from, &obs_to)
+        .await?
+        .iter()
+        .rev()
+        .map(|o| o.temperature_c)
+        .find(|t| t.is_finite());
+    let temperature_c = observed.unwrap_or_else(|| {
+        forecast
+            .iter()
+            .min_by_key(|p| (p.timestamp - now).num_seconds().unsigned_abs())
+            .filter(|p| (p.timestamp - now).num_hours().abs() <= 1)
+            .map(|p| p.temperature_c)
+            .unwrap_or(f64::NAN)
+    });
+
+    let slot = now.timestamp() - now.timestamp() % 900;
+    let slot_from = chrono::DateTime::from_timestamp(slot, 0).unwrap();
+    let price_cents_kwh = db
+        .get_electricity_prices(
+            &slot_from.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            &(slot_from + chrono::Duration::minutes(15))
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
+        )
+        .await?
+        .first()
+        .map(|p| config.price_components.total(p.price_cents_kwh, now, tz));
+
+    let effective_avg = config
+        .temperature_adjustment()
+        .weighted_avg(&forecast, tz, 0.9, 24, 3);
+    let mut radiators = Vec::new();
+    for radiator in db.list_radiators().await? {
+        let recommended = radiator_recommendation(db, config, &radiator, effective_avg)
+            .await?
+            .setting;
+        let current = db.get_radiator_setting(radiator.id).await?;
+        radiators.push(mqtt::RadiatorStatus {
+            radiator,
+            recommended,
+            current,
+        });
+    }
+    let max_setting = db
+        .get_heating_curve()
+        .await?
+        .settings()
+        .into_iter()
+        .fold(0.0, f64::max);
+
+    mqtt.publish_state(&mqtt::State {
+        temperature_c,
+        min_temp_c,
+        max_temp_c,
+        price_cents_kwh,
+        max_setting,
+        radiators,
+    })
+}
+
+/// JSON push payload for a radiator notification, with an action so the
+/// service worker can apply `setting` straight from the notification. The
+/// tag keeps each radiator's notification from replacing the others'.
+fn radiator_payload(message: &str, radiator: &db::Radiator, setting: f64) -> String {
+    serde_json::json!({
+        "body": message,
+        "tag": format!("radiator-{}", radiator.id),
+        "action": {
+            "title": format!("Set to {}", heating::setting_label(setting)),
+            "radiator": setting,
+            "radiator_id": radiator.id,
         },
-        _ => true,
+    })
+    .to_string()
+}
+
+/// What the preheating plan asks of a radiator this hour.
+enum PreheatStep {
+    /// Not a planned hour, the regular recommendation applies.
+    Unplanned,
+    /// A planned hour with nothing to send.
+    Quiet,
+    Notify {
+        message: String,
+        setting: f64,
+    },
+}
+
+/// Look up the current hour in `plan`. Shifted hours are logged so the hour
+/// after a shift can send the radiator back to its base setting, and stored
+/// so later plans know the heat they banked.
+async fn preheat_step(
+    db: &db::Db,
+    radiator: &db::Radiator,
+    plan: &[preheat::PlanHour],
+    current: Option<f64>,
+    tz: chrono_tz::Tz,
+) -> anyhow::Result<PreheatStep> {
+    let now = Utc::now();
+    let hour_ts = now.timestamp() / 3600 * 3600;
+    let Some(hour) = plan.first().filter(|h| h.timestamp.timestamp() == hour_ts) else {
+        return Ok(PreheatStep::Unplanned);
     };
-    if needs_fetch {
-        match electricity::fetch_eprices().await {
-            Ok(prices) => {
-                info!("Fetched {} electricity price entries", prices.len());
-                if let Err(e) = db.upsert_electricity_prices(&prices).await {
-                    error!("Failed to upsert electricity prices: {e}");
+    let today = now.with_timezone(&tz).date_naive();
+    let shifted_key = |ts: i64| format!("preheat_{}_{ts}", radiator.slug);
+    let current_str = current
+        .map(|c| format!("{:.1}", c))
+        .unwrap_or_else(|| "unknown".to_string());
+    let is_current = |setting: f64| current.is_some_and(|c| (c - setting).abs() < 0.01);
+
+    if hour.shift == Shift::Hold {
+        let previous = hour_ts - 3600;
+        let previous_date = chrono::DateTime::from_timestamp(previous, 0)
+            .unwrap()
+            .with_timezone(&tz)
+            .date_naive();
+        if !db
+            .already_notified(&shifted_key(previous), previous_date)
+            .await?
+        {
+            return Ok(PreheatStep::Unplanned);
+        }
+        let end_key = format!("{}_end", shifted_key(hour_ts));
+        if db.already_notified(&end_key, today).await? || is_current(hour.base) {
+            return Ok(PreheatStep::Quiet);
+        }
+        db.log_notification(&end_key, today).await?;
+        return Ok(PreheatStep::Notify {
+            message: format!(
+                "{}: back to {} → {:.1}, preheating done",
+                radiator.name, current_str, hour.base
+            ),
+            setting: hour.base,
+        });
+    }
+
+    db.insert_preheat_hour(&db::PreheatHour {
+        radiator_id: radiator.id,
+        timestamp: hour.timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        base: hour.base,
+        setting: hour.setting,
+        shift: hour.shift.as_str().to_string(),
+    })
+    .await?;
+    let key = shifted_key(hour_ts);
+    if db.already_notified(&key, today).await? {
+        return Ok(PreheatStep::Quiet);
+    }
+    db.log_notification(&key, today).await?;
+    if is_current(hour.setting) {
+        return Ok(PreheatStep::Quiet);
+    }
+    let until = plan
+        .iter()
+        .find(|h| h.shift != hour.shift)
+        .map(|h| h.timestamp.with_timezone(&tz).format("%H:%M").to_string())
+        .unwrap_or_else(|| "later".to_string());
+    let (verb, price_kind) = match hour.shift {
+        Shift::Raise => ("Raise", "cheap"),
+        _ => ("Lower", "expensive"),
+    };
+    Ok(PreheatStep::Notify {
+        message: format!(
+            "{verb} {} now: {} → {:.1}, {price_kind} {:.1} snt until {until}",
+            radiator.name, current_str, hour.setting, hour.price_cents_kwh
+        ),
+        setting: hour.setting,
+    })
+}
+
+/// Start and end of tomorrow in local time.
+fn tomorrow_bounds(
+    tz: chrono_tz::Tz,
+    now: chrono::DateTime<Utc>,
+) -> Option<(NaiveDate, chrono::DateTime<Utc>, chrono::DateTime<Utc>)> {
+    let tomorrow = now.with_timezone(&tz).date_naive().succ_opt()?;
+    let start_of = |date: NaiveDate| {
+        tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()
+            .map(|dt| dt.to_utc())
+    };
+    Some((
+        tomorrow,
+        start_of(tomorrow)?,
+        start_of(tomorrow.succ_opt()?)?,
+    ))
+}
+
+/// Whether stored prices cover all of tomorrow.
+async fn tomorrow_prices_stored(db: &db::Db, tz: chrono_tz::Tz) -> anyhow::Result<bool> {
+    let Some((_, _, end)) = tomorrow_bounds(tz, Utc::now()) else {
+        return Ok(false);
+    };
+    let latest = db.get_latest_electricity_timestamp().await?;
+    Ok(latest
+        .and_then(|latest| chrono::DateTime::parse_from_rfc3339(&latest).ok())
+        .is_some_and(|latest| latest.to_utc() + chrono::Duration::minutes(15) >= end))
+}
+
+/// Whether it's publication time and tomorrow's prices haven't arrived.
+async fn awaiting_tomorrow_prices(db: &db::Db, config: &Config) -> bool {
+    let hour = Utc::now().with_timezone(&config.tz).hour();
+    let (from, to) = PRICE_PUBLICATION_HOURS;
+    if hour < from || hour >= to {
+        return false;
+    }
+    match tomorrow_prices_stored(db, config.tz).await {
+        Ok(stored) => !stored,
+        Err(e) => {
+            error!("Failed to check for tomorrow's prices: {e}");
+            false
+        }
+    }
+}
+
+/// Tomorrow's average total price with its cheapest and most expensive
+/// `PRICE_BLOCK_HOURS` blocks, once all of tomorrow's prices are stored.
+async fn build_tomorrow_prices(
+    db: &db::Db,
+    config: &Config,
+) -> anyhow::Result<Option<(NaiveDate, String)>> {
+    let tz = config.tz;
+    let Some((tomorrow, start, end)) = tomorrow_bounds(tz, Utc::now()) else {
+        return Ok(None);
+    };
+    if !tomorrow_prices_stored(db, tz).await? {
+        return Ok(None);
+    }
+    let slots = cheapest_window::load_slots(db, config, start, end).await?;
+    if slots.is_empty() {
+        return Ok(None);
+    }
+    let avg = slots.iter().map(|s| s.price_cents_kwh).sum::<f64>() / slots.len() as f64;
+    let block = chrono::Duration::hours(PRICE_BLOCK_HOURS);
+    let by_price = |a: &cheapest_window::Window, b: &cheapest_window::Window| {
+        a.avg_price_cents_kwh.total_cmp(&b.avg_price_cents_kwh)
+    };
+    let cheapest = cheapest_window::windows(&slots, block, start, end, None).min_by(by_price);
+    let priciest = cheapest_window::windows(&slots, block, start, end, None).max_by(by_price);
+    let describe = |label: &str, window: Option<cheapest_window::Window>| {
+        window
+            .map(|w| {
+                format!(
+                    ", {label} {PRICE_BLOCK_HOURS}h {}–{} at {:.2} snt",
+                    w.start.with_timezone(&tz).format("%H:%M"),
+                    w.end.with_timezone(&tz).format("%H:%M"),
+                    w.avg_price_cents_kwh
+                )
+            })
+            .unwrap_or_default()
+    };
+    Ok(Some((
+        tomorrow,
+        format!(
+            "Tomorrow's prices are out: avg {avg:.2} snt{}{}",
+            describe("cheapest", cheapest),
+            describe("priciest", priciest)
+        ),
+    )))
+}
+
+/// Alert on newly fetched `prices` crossing the configured thresholds, and
+/// send the overview of tomorrow's prices once they're all in. Each is sent
+/// once per kind and day.
+async fn notify_new_prices(
+    db: &db::Db,
+    config: &Config,
+    prices: &[(String, f64)],
+) -> anyhow::Result<()> {
+    let mut notifications: Vec<(&str, NaiveDate, String)> = Vec::new();
+    if config.price_alerts.is_configured() {
+        notifications.extend(
+            price_alerts::evaluate(
+                &config.price_alerts,
+                prices,
+                &config.price_components,
+                config.tz,
+                Utc::now(),
+            )
+            .into_iter()
+            .map(|alert| (alert.kind, alert.date, alert.message)),
+        );
+    }
+    if let Some((tomorrow, message)) = build_tomorrow_prices(db, config).await? {
+        notifications.push(("tomorrow_prices", tomorrow, message));
+    }
+
+    let subscriptions = db.list_subscriptions().await?;
+    let vapid = VapidConfig::from_config(config);
+    for (kind, date, message) in notifications {
+        if db.already_notified(kind, date).await? {
+            continue;
+        }
+        info!("Sending price notification: {message}");
+        notify::send_all_logged(&subscriptions, &message, &vapid, "Price notification").await;
+        db.log_notification(kind, date).await?;
+    }
+    Ok(())
+}
+
+/// Fetch and store the latest prices and notify about them.
+async fn fetch_and_store_prices(db: &db::Db, config: &Config) {
+    match electricity::Provider::from_config(config)
+        .fetch_prices()
+        .await
+    {
+        Ok(prices) => {
+            info!("Fetched {} electricity price entries", prices.len());
+            match db.upsert_electricity_prices(&prices).await {
+                Ok(()) => {
+                    if let Err(e) = notify_new_prices(db, config, &prices).await {
+                        error!("Failed to send price notifications: {e}");
+                    }
                 }
-            }
-            Err(e) => {
-                error!("Failed to fetch electricity prices: {e}");
+                Err(e) => error!("Failed to upsert electricity prices: {e}"),
             }
         }
+        Err(e) => {
+            error!("Failed to fetch electricity prices: {e}");
+        }
     }
+}
 
-    // Weather observations
-    let obs_stale = match db.get_latest_observation_timestamp().await {
+async fn run_check(db: &db::Db, config: &Config) -> anyhow::Result<()> {
+    let needs_fetch = match db.get_latest_electricity_timestamp().await {
         Ok(Some(latest)) => match chrono::DateTime::parse_from_rfc3339(&latest) {
             Ok(latest_dt) => {
-                let hours_ago = (Utc::now() - latest_dt.to_utc()).num_hours();
-                info!("Latest weather observation is {hours_ago}h old");
-                hours_ago >= 2
+                let hours_ahead = (latest_dt.to_utc() - Utc::now()).num_hours();
+                info!("Latest electricity price is {hours_ahead}h ahead");
+                hours_ahead < 12
             }
             Err(_) => true,
         },
         _ => true,
     };
-    if obs_stale {
-        match weather::fetch_observations(&config.fmi_sid).await {
-            Ok(points) => {
-                info!("Fetched {} weather observation points", points.len());
-                if let Err(e) = db.upsert_weather_observations(&points).await {
-                    error!("Failed to upsert weather observations: {e}");
-                }
-            }
-            Err(e) => {
-                error!("Failed to fetch weather observations: {e}");
-            }
-        }
-        if let Some(wind_sid) = &config.fmi_sid_wind {
-            match weather::fetch_observations(wind_sid).await {
-                Ok(points) => {
-                    info!("Fetched {} wind observation points from {wind_sid}", points.len());
-                    if let Err(e) = db.merge_wind_observations(&points).await {
-                        error!("Failed to merge wind observations: {e}");
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to fetch wind observations from {wind_sid}: {e}");
-                }
-            }
-        }
+    if needs_fetch {
+        fetch_and_store_prices(db, config).await;
     }
 
-    info!("Scheduler: fetching forecast for {}", config.fmi_sid);
+    for location in db.list_locations().await? {
+        refresh_location(db, config, &location).await;
+    }
 
-    let forecast = match weather::fetch_forecast(&config.fmi_sid).await {
+    let location = db.default_location().await?;
+    let forecast = match load_forecast(db, config, &location).await {
         Ok(f) => f,
         Err(e) => {
-            info!("Failed to fetch forecast: {e}");
+            info!("No forecast available: {e}");
             return Ok(());
         }
     };
 
     let now = Utc::now();
-    let tz = config.tz;
+    let tz = location.tz();
     let today = now.with_timezone(&tz).date_naive();
 
     let next_24h: Vec<_> = forecast
@@ -319,11 +925,11 @@ async fn run_check(db: &db::Db, config: &Config) -> anyhow::Result<()> {
         .fold(f64::NEG_INFINITY, f64::max);
 
     let weighted_avg = ForecastPoint::weighted_avg_temperature(&forecast, 0.9, 24, 3);
-    let recommended_setting = temp_to_radiator_setting(weighted_avg);
+    let effective_avg = config
+        .temperature_adjustment()
+        .weighted_avg(&forecast, tz, 0.9, 24, 3);
 
-    tracing::debug!(
-        "min_temp {min_temp}, max_temp {max_temp}, weighted_avg {weighted_avg:.1}, recommended_setting {recommended_setting:.2}"
-    );
+    tracing::debug!("min_temp {min_temp}, max_temp {max_temp}");
 
     let subscriptions = db.list_subscriptions().await?;
 
@@ -331,11 +937,7 @@ async fn run_check(db: &db::Db, config: &Config) -> anyhow::Result<()> {
         info!("No push subscribers, skipping notifications");
     }
 
-    let vapid = VapidConfig {
-        subject: config.vapid_subject.clone(),
-        public_key_b64: config.vapid_public_key.clone(),
-        private_key_b64: config.vapid_private_key.clone(),
-    };
+    let vapid = VapidConfig::from_config(config);
 
     // Daily summary
     let local_hour = now.with_timezone(&tz).hour();
@@ -345,49 +947,98 @@ async fn run_check(db: &db::Db, config: &Config) -> anyhow::Result<()> {
         if !already_sent {
             let message = build_daily_summary(db, config).await?;
             info!("Sending daily summary: {message}");
-            let results = notify::send_all(&subscriptions, &message, &vapid).await;
-            let success_count = results.iter().filter(|r| r.is_ok()).count();
-            info!(
-                "Daily summary sent to {}/{} subscribers",
-                success_count,
-                subscriptions.len()
-            );
+            notify::send_all_logged(&subscriptions, &message, &vapid, "Daily summary").await;
             db.log_notification(summary_key, today).await?;
         }
     }
 
     // Radiator adjustment check
-    if recommended_setting.is_finite() {
-        let current_setting = db.get_radiator_setting().await?;
+    let curve = db.get_heating_curve().await?;
+    for radiator in db.list_radiators().await? {
+        let recommendation = radiator_recommendation(db, config, &radiator, effective_avg).await?;
+        let recommended_setting = recommendation.setting;
+        let current_state = db.get_radiator_state(radiator.id).await?;
+        info!(
+            "Radiator decision for {}: weighted avg {weighted_avg:.1}°C, effective {effective_avg:.1}°C, offset {:+.1}°C, current {}, curve {:.1}, deadband ±{:.1}°C, min dwell {}h → {:.1} ({})",
+            radiator.slug,
+            radiator.curve_offset_c,
+            current_state
+                .as_ref()
+                .map(|s| format!("{:.1} since {}", s.setting, s.updated_at))
+                .unwrap_or_else(|| "unknown".to_string()),
+            recommendation.curve_setting,
+            config.heating_deadband_c,
+            config.radiator_min_dwell_hours,
+            recommended_setting,
+            recommendation.reason.as_str(),
+        );
+        let current_setting = current_state.map(|s| s.setting);
+
+        // The preheating plan overrides the recommendation while it shifts heat
+        let plan = preheat::load_plan(db, config, &location, &radiator, &curve, &forecast).await?;
+        match preheat_step(db, &radiator, &plan, current_setting, tz).await? {
+            PreheatStep::Unplanned => {}
+            PreheatStep::Quiet => continue,
+            PreheatStep::Notify { message, setting } => {
+                info!("Sending preheat notification: {message}");
+                let payload = radiator_payload(&message, &radiator, setting);
+                notify::send_all_logged(&subscriptions, &payload, &vapid, "Preheat notification")
+                    .await;
+                continue;
+            }
+        }
+
+        if !recommended_setting.is_finite() {
+            continue;
+        }
+
         let diff = if let Some(current) = current_setting {
             (recommended_setting - current).abs()
         } else {
             f64::INFINITY
         };
 
-        if diff >= 0.5 {
-            let radiator_key = format!("radiator_{:.1}", recommended_setting.round());
+        // Any other curve position is a change; the deadband and dwell time
+        // already keep the recommendation from flapping
+        if diff >= 0.01 {
+            let radiator_key = format!("radiator_{}_{}", radiator.slug, recommended_setting);
             let already_sent = db.already_notified(&radiator_key, today).await?;
             if !already_sent {
                 let current_str = current_setting
                     .map(|c| format!("{:.1}", c))
                     .unwrap_or_else(|| "unknown".to_string());
                 let message = format!(
-                    "Radiator: {:.1} → {:.1} (avg {:.0}°C next 24h)",
-                    current_str, recommended_setting, weighted_avg
+                    "{}: {:.1} → {:.1} (avg {:.0}°C, feels {:.0}°C next 24h)",
+                    radiator.name, current_str, recommended_setting, weighted_avg, effective_avg
                 );
                 info!("Sending radiator notification: {message}");
-                let results = notify::send_all(&subscriptions, &message, &vapid).await;
-                let success_count = results.iter().filter(|r| r.is_ok()).count();
-                info!(
-                    "Radiator notification sent to {}/{} subscribers",
-                    success_count,
-                    subscriptions.len()
-                );
+                let payload = radiator_payload(&message, &radiator, recommended_setting);
+                notify::send_all_logged(&subscriptions, &payload, &vapid, "Radiator notification")
+                    .await;
                 db.log_notification(&radiator_key, today).await?;
             }
         }
     }
 
+    // Heating curve learning
+    if let Some(target_c) = config.indoor_target_c {
+        let proposal_key = "curve_proposal";
+        if local_hour == config.summary_hour && !db.already_notified(proposal_key, today).await? {
+            db.log_notification(proposal_key, today).await?;
+            match curve_tuning::create_proposal(db, &location, target_c, CURVE_LEARNING_DAYS, true)
+                .await
+            {
+                Ok(Some(id)) => {
+                    let message = "New heating curve proposal, review it in Settings";
+                    info!("Sending curve proposal {id} notification");
+                    notify::send_all_logged(&subscriptions, message, &vapid, "Curve proposal")
+                        .await;
+                }
+                Ok(None) => {}
+                Err(e) => info!("No heating curve proposal: {e}"),
+            }
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,511 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+
+use super::{ForecastPoint, ForecastRun, WeatherProvider};
+
+pub const DEFAULT_WFS_URL: &str = "https://opendata.fmi.fi/wfs";
+
+/// FMI open data WFS. Sites are FMISIDs, `lat,lon` pairs or place names.
+#[derive(Debug, Clone)]
+pub struct Fmi {
+    pub wfs_url: String,
+}
+
+impl WeatherProvider for Fmi {
+    async fn fetch_forecast(&self, site: &str) -> Result<ForecastRun> {
+        fetch_forecast(&self.wfs_url, site).await
+    }
+
+    async fn fetch_observations(
+        &self,
+        site: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<ForecastPoint>> {
+        fetch_observations(&self.wfs_url, site, start, end).await
+    }
+
+    fn max_observation_span(&self) -> Duration {
+        Duration::hours(MAX_HOURLY_OBSERVATION_HOURS)
+    }
+}
+
+/// FMI rejects hourly observation queries spanning more than 744 hours (31 days).
+const MAX_HOURLY_OBSERVATION_HOURS: i64 = 744;
+
+/// The stored-query parameter selecting `site`: `fmisid=` for numeric IDs,
+/// `latlon=` for coordinate pairs and `place=` for anything else.
+fn site_param(site: &str) -> String {
+    let site = site.trim();
+    if !site.is_empty() && site.chars().all(|c| c.is_ascii_digit()) {
+        return format!("fmisid={site}");
+    }
+    if let Some((lat, lon)) = site.split_once(',') {
+        if lat.trim().parse::<f64>().is_ok() && lon.trim().parse::<f64>().is_ok() {
+            return format!("latlon={},{}", lat.trim(), lon.trim());
+        }
+    }
+    format!("place={}", encode_query_value(site))
+}
+
+fn encode_query_value(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+async fn fetch_forecast(wfs_url: &str, site: &str) -> Result<ForecastRun> {
+    let client = reqwest::Client::builder().use_rustls_tls().build()?;
+
+    let now = Utc::now();
+    let hour_ts = now.timestamp() - (now.timestamp() % 3600);
+    let current_hour = DateTime::from_timestamp(hour_ts, 0).unwrap();
+    let start = current_hour.format("%Y-%m-%dT%H:%M:%S.000Z");
+    let end = (now + Duration::days(7)).format("%Y-%m-%dT%H:%M:%S.000Z");
+
+    let params = FORECAST_FIELDS.all().join(",");
+
+    let url = format!(
+        "{}?request=getFeature&storedquery_id=fmi::forecast::edited::weather::scandinavia::point::multipointcoverage&parameters={}&starttime={}&endtime={}&{}",
+        wfs_url, params, start, end, site_param(site)
+    );
+
+    tracing::debug!("FMI request: {url}");
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("FMI API returned status {}", resp.status()));
+    }
+    let xml = resp.text().await?;
+    tracing::trace!("FMI response: {} bytes", xml.len());
+    let points = parse_multipointcoverage(&xml)?;
+    let issued_at = extract_result_time(&xml).unwrap_or(now);
+    let place_name = extract_place_name(&xml);
+    Ok(ForecastRun {
+        issued_at,
+        place_name,
+        points,
+    })
+}
+
+async fn fetch_observations(
+    wfs_url: &str,
+    site: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<ForecastPoint>> {
+    let client = reqwest::Client::builder().use_rustls_tls().build()?;
+
+    let start = start.format("%Y-%m-%dT%H:%M:%S.000Z");
+    let end = end.format("%Y-%m-%dT%H:%M:%S.000Z");
+
+    let params = OBSERVATION_FIELDS.all().join(",");
+
+    let url = format!(
+        "{}?request=getFeature&storedquery_id=fmi::observations::weather::hourly::multipointcoverage&parameters={}&starttime={}&endtime={}&{}",
+        wfs_url, params, start, end, site_param(site)
+    );
+
+    tracing::debug!("FMI observations request: {url}");
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("FMI API returned status {}", resp.status()));
+    }
+    let xml = resp.text().await?;
+    tracing::trace!("FMI observations response: {} bytes", xml.len());
+    parse_observation_coverage(&xml)
+}
+
+/// A decoded multipointcoverage row: the position's epoch time and its values
+/// keyed by the field names declared in the response's `swe:DataRecord`.
+struct CoverageRow {
+    epoch: i64,
+    values: HashMap<String, f64>,
+}
+
+impl CoverageRow {
+    fn get(&self, field: &str) -> f64 {
+        self.values.get(field).copied().unwrap_or(f64::NAN)
+    }
+
+    fn get_opt(&self, field: Option<&str>) -> f64 {
+        field.map(|f| self.get(f)).unwrap_or(f64::NAN)
+    }
+
+    fn into_point(self, fields: &PointFields) -> Result<Option<ForecastPoint>> {
+        let temperature_c = self.get(fields.temperature);
+        if temperature_c.is_nan() {
+            return Ok(None);
+        }
+
+        let timestamp = DateTime::from_timestamp(self.epoch, 0)
+            .ok_or_else(|| anyhow!("Invalid timestamp {}", self.epoch))?;
+
+        Ok(Some(ForecastPoint {
+            timestamp,
+            temperature_c,
+            wind_speed_ms: self.get(fields.wind_speed),
+            precipitation_mm: self.get(fields.precipitation),
+            humidity: self.get(fields.humidity),
+            wind_direction: self.get(fields.wind_direction),
+            cloud_cover: self.get_opt(fields.cloud_cover) * fields.cloud_cover_scale,
+            pressure_hpa: self.get_opt(fields.pressure),
+            dew_point_c: self.get_opt(fields.dew_point),
+            wind_gust_ms: self.get_opt(fields.wind_gust),
+            weather_symbol: self.get_opt(fields.weather_symbol),
+        }))
+    }
+}
+
+/// FMI field names that map onto `ForecastPoint` members. `None` means the
+/// stored query has no equivalent and the member is left as NaN.
+struct PointFields {
+    temperature: &'static str,
+    wind_speed: &'static str,
+    precipitation: &'static str,
+    humidity: &'static str,
+    wind_direction: &'static str,
+    cloud_cover: Option<&'static str>,
+    /// Factor converting `cloud_cover` to percent.
+    cloud_cover_scale: f64,
+    pressure: Option<&'static str>,
+    dew_point: Option<&'static str>,
+    wind_gust: Option<&'static str>,
+    weather_symbol: Option<&'static str>,
+}
+
+impl PointFields {
+    fn all(&self) -> Vec<&'static str> {
+        [
+            Some(self.temperature),
+            Some(self.wind_speed),
+            Some(self.precipitation),
+            Some(self.humidity),
+            Some(self.wind_direction),
+            self.cloud_cover,
+            self.pressure,
+            self.dew_point,
+            self.wind_gust,
+            self.weather_symbol,
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+const FORECAST_FIELDS: PointFields = PointFields {
+    temperature: "Temperature",
+    wind_speed: "WindSpeedMS",
+    precipitation: "Precipitation1h",
+    humidity: "Humidity",
+    wind_direction: "WindDirection",
+    cloud_cover: Some("TotalCloudCover"),
+    cloud_cover_scale: 1.0,
+    pressure: Some("Pressure"),
+    dew_point: Some("DewPoint"),
+    wind_gust: Some("WindGust"),
+    weather_symbol: Some("WeatherSymbol3"),
+};
+
+const OBSERVATION_FIELDS: PointFields = PointFields {
+    temperature: "TA_PT1H_AVG",
+    wind_speed: "WS_PT1H_AVG",
+    precipitation: "PRA_PT1H_ACC",
+    humidity: "RH_PT1H_AVG",
+    wind_direction: "WD_PT1H_AVG",
+    // Observed in oktas
+    cloud_cover: Some("N_PT1H_AVG"),
+    cloud_cover_scale: 100.0 / 8.0,
+    pressure: Some("PA_PT1H_AVG"),
+    dew_point: Some("TD_PT1H_AVG"),
+    wind_gust: Some("WG_PT1H_MAX"),
+    // Stations report present weather codes, which don't map onto symbols
+    weather_symbol: None,
+};
+
+/// Parse the hourly observation multipointcoverage XML.
+fn parse_observation_coverage(xml: &str) -> Result<Vec<ForecastPoint>> {
+    let points = parse_points(xml, &OBSERVATION_FIELDS)?;
+    tracing::trace!("Parsed {} observation points", points.len());
+    Ok(points)
+}
+
+fn parse_multipointcoverage(xml: &str) -> Result<Vec<ForecastPoint>> {
+    let points = parse_points(xml, &FORECAST_FIELDS)?;
+    tracing::trace!("Parsed {} forecast points", points.len());
+    Ok(points)
+}
+
+fn parse_points(xml: &str, fields: &PointFields) -> Result<Vec<ForecastPoint>> {
+    let rows = extract_positions_and_data(xml, &fields.all())?;
+    let mut points = Vec::with_capacity(rows.len());
+    for row in rows {
+        if let Some(point) = row.into_point(fields)? {
+            points.push(point);
+        }
+    }
+    Ok(points)
+}
+
+/// Extract rows from FMI multipointcoverage XML, decoding each data block row
+/// by the field names declared in `swe:DataRecord`. Fails if any of
+/// `required` is not declared.
+fn extract_positions_and_data(xml: &str, required: &[&str]) -> Result<Vec<CoverageRow>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut in_positions = false;
+    let mut in_datablock = false;
+    let mut in_datarecord = false;
+    let mut positions_text = String::new();
+    let mut datablock_text = String::new();
+    let mut field_names: Vec<String> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                let name = e.name();
+                let local = name.local_name();
+                match local.as_ref() {
+                    b"positions" => in_positions = true,
+                    b"DataBlock" => in_datablock = true,
+                    b"DataRecord" => in_datarecord = true,
+                    b"field" if in_datarecord => field_names.push(field_name(&e)?),
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(e)) if in_datarecord && e.name().local_name().as_ref() == b"field" => {
+                field_names.push(field_name(&e)?);
+            }
+            Ok(Event::End(e)) => {
+                let name = e.name();
+                let local = name.local_name();
+                match local.as_ref() {
+                    b"positions" => in_positions = false,
+                    b"DataBlock" => in_datablock = false,
+                    b"DataRecord" => in_datarecord = false,
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) => {
+                if in_positions {
+                    positions_text.push_str(&e.unescape()?);
+                }
+                if in_datablock {
+                    datablock_text.push_str(&e.unescape()?);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(anyhow!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if positions_text.is_empty() {
+        return Err(anyhow!("No positions found in FMI response"));
+    }
+    if datablock_text.is_empty() {
+        return Err(anyhow!("No data block found in FMI response"));
+    }
+    if field_names.is_empty() {
+        return Err(anyhow!("No swe:field names found in FMI response"));
+    }
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|r| !field_names.iter().any(|f| f == *r))
+        .copied()
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "FMI response is missing required field(s) {} (declared: {})",
+            missing.join(", "),
+            field_names.join(", ")
+        ));
+    }
+
+    let pos_tokens: Vec<&str> = positions_text.split_whitespace().collect();
+    let mut timestamps: Vec<i64> = Vec::new();
+    let mut i = 0;
+    while i + 2 < pos_tokens.len() {
+        let epoch: i64 = pos_tokens[i + 2].parse()?;
+        timestamps.push(epoch);
+        i += 3;
+    }
+
+    let data_tokens: Vec<&str> = datablock_text.split_whitespace().collect();
+    let row_len = field_names.len();
+    let n = timestamps.len();
+    if data_tokens.len() < n * row_len {
+        return Err(anyhow!(
+            "Data block has {} tokens but expected at least {} ({}*{})",
+            data_tokens.len(),
+            n * row_len,
+            n,
+            row_len
+        ));
+    }
+
+    let rows = timestamps
+        .into_iter()
+        .zip(data_tokens.chunks(row_len))
+        .map(|(epoch, tokens)| CoverageRow {
+            epoch,
+            values: field_names
+                .iter()
+                .zip(tokens)
+                .map(|(name, token)| (name.clone(), parse_val(token)))
+                .collect(),
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+fn field_name(e: &BytesStart) -> Result<String> {
+    let attr = e
+        .try_get_attribute("name")?
+        .ok_or_else(|| anyhow!("swe:field without a name attribute in FMI response"))?;
+    Ok(attr.unescape_value()?.into_owned())
+}
+
+/// Extract the model run time (`om:resultTime`) from an FMI response.
+fn extract_result_time(xml: &str) -> Option<DateTime<Utc>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut in_result_time = false;
+    let mut in_time_position = false;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.name().local_name().as_ref() {
+                b"resultTime" => in_result_time = true,
+                b"timePosition" => in_time_position = in_result_time,
+                _ => {}
+            },
+            Ok(Event::End(e)) => match e.name().local_name().as_ref() {
+                b"resultTime" => in_result_time = false,
+                b"timePosition" => in_time_position = false,
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_time_position => {
+                let text = e.unescape().ok()?;
+                return DateTime::parse_from_rfc3339(text.trim())
+                    .ok()
+                    .map(|dt| dt.to_utc());
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Extract the station/place name (`gml:name` with the `locationcode/name`
+/// code space) from the location block of an FMI response.
+fn extract_place_name(xml: &str) -> Option<String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut in_location = false;
+    let mut in_name = false;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.name().local_name().as_ref() {
+                b"Location" => in_location = true,
+                b"name" if in_location => {
+                    in_name = e
+                        .try_get_attribute("codeSpace")
+                        .ok()
+                        .flatten()
+                        .is_some_and(|a| a.value.ends_with(b"locationcode/name"));
+                }
+                _ => {}
+            },
+            Ok(Event::End(e)) => match e.name().local_name().as_ref() {
+                b"Location" => in_location = false,
+                b"name" => in_name = false,
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_name => {
+                let text = e.unescape().ok()?;
+                let text = text.trim();
+                return (!text.is_empty()).then(|| text.to_string());
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+fn parse_val(s: &str) -> f64 {
+    s.parse().unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REORDERED: &str = include_str!("testdata/fmi_forecast_reordered.xml");
+    const MISSING_FIELD: &str = include_str!("testdata/fmi_forecast_missing_field.xml");
+
+    #[test]
+    fn maps_columns_by_declared_field_name() {
+        let points = parse_multipointcoverage(REORDERED).unwrap();
+        assert_eq!(points.len(), 2);
+
+        let first = &points[0];
+        assert_eq!(first.timestamp.to_rfc3339(), "2026-10-17T07:00:00+00:00");
+        assert_eq!(first.temperature_c, 4.5);
+        assert_eq!(first.wind_speed_ms, 3.2);
+        assert_eq!(first.precipitation_mm, 0.0);
+        assert_eq!(first.humidity, 81.0);
+        assert_eq!(first.wind_direction, 230.0);
+        assert_eq!(first.cloud_cover, 12.0);
+        assert_eq!(first.pressure_hpa, 1012.3);
+        assert_eq!(first.dew_point_c, 1.5);
+        assert_eq!(first.wind_gust_ms, 6.1);
+        assert_eq!(first.weather_symbol, 1.0);
+
+        let second = &points[1];
+        assert_eq!(second.temperature_c, 3.9);
+        assert_eq!(second.precipitation_mm, 0.4);
+        assert_eq!(second.weather_symbol, 31.0);
+        assert!(second.wind_gust_ms.is_nan());
+    }
+
+    #[test]
+    fn reads_issue_time_and_place() {
+        assert_eq!(
+            extract_result_time(REORDERED).map(|dt| dt.to_rfc3339()),
+            Some("2026-10-17T06:00:00+00:00".to_string())
+        );
+        assert_eq!(extract_place_name(REORDERED).as_deref(), Some("Helsinki"));
+    }
+
+    #[test]
+    fn missing_required_field_is_an_error() {
+        let error = parse_multipointcoverage(MISSING_FIELD).unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.starts_with("FMI response is missing required field(s) Humidity "),
+            "{message}"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::{Config, WeatherProviderKind};
+
+pub mod fmi;
+pub mod open_meteo;
+
+#[derive(Debug, Clone)]
+pub struct ForecastPoint {
+    pub timestamp: DateTime<Utc>,
+    pub temperature_c: f64,
+    pub wind_speed_ms: f64,
+    pub precipitation_mm: f64,
+    pub humidity: f64,
+    pub wind_direction: f64,
+    pub cloud_cover: f64,
+    pub pressure_hpa: f64,
+    pub dew_point_c: f64,
+    pub wind_gust_ms: f64,
+    /// FMI WeatherSymbol3 code.
+    pub weather_symbol: f64,
+}
+
+/// A single forecast run as issued by the weather provider.
+#[derive(Debug, Clone)]
+pub struct ForecastRun {
+    pub issued_at: DateTime<Utc>,
+    /// Station or place name the provider resolved the site to, if it reports one.
+    pub place_name: Option<String>,
+    pub points: Vec<ForecastPoint>,
+}
+
+impl ForecastPoint {
+    pub fn weighted_avg_temperature(
+        points: &[Self],
+        decay: f64,
+        horizon_hours: usize,
+        skip_hours: usize,
+    ) -> f64 {
+        Self::weighted_avg_by(points, decay, horizon_hours, skip_hours, |p| {
+            p.temperature_c
+        })
+    }
+
+    /// Exponentially decaying average of `value` over the next
+    /// `horizon_hours` points after skipping `skip_hours`. Non-finite values
+    /// are left out.
+    pub fn weighted_avg_by(
+        points: &[Self],
+        decay: f64,
+        horizon_hours: usize,
+        skip_hours: usize,
+        value: impl Fn(&Self) -> f64,
+    ) -> f64 {
+        if points.is_empty() {
+            return f64::NAN;
+        }
+
+        let n = points.len().min(skip_hours + horizon_hours);
+        let mut sum = 0.0;
+        let mut weight_sum = 0.0;
+
+        for (i, point) in points
+            .iter()
+            .skip(skip_hours)
+            .take(n.saturating_sub(skip_hours))
+            .enumerate()
+        {
+            let v = value(point);
+            if !v.is_finite() {
+                continue;
+            }
+            let w = decay.powi(i as i32);
+            sum += v * w;
+            weight_sum += w;
+        }
+
+        if weight_sum > 0.0 {
+            sum / weight_sum
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+/// A source of hourly forecasts and observations for a site. What a site
+/// identifier means is up to the provider (an FMISID, `lat,lon` or place
+/// name for FMI, `lat,lon` for Open-Meteo).
+pub trait WeatherProvider {
+    async fn fetch_forecast(&self, site: &str) -> Result<ForecastRun>;
+    async fn fetch_observations(
+        &self,
+        site: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<ForecastPoint>>;
+
+    /// Longest `start..end` span a single observations query may cover.
+    fn max_observation_span(&self) -> Duration;
+}
+
+/// The provider selected in `Config`.
+#[derive(Debug, Clone)]
+pub enum Provider {
+    Fmi(fmi::Fmi),
+    OpenMeteo(open_meteo::OpenMeteo),
+}
+
+impl Provider {
+    pub fn from_config(config: &Config) -> Self {
+        match config.weather_provider {
+            WeatherProviderKind::Fmi => Provider::Fmi(fmi::Fmi {
+                wfs_url: config.fmi_wfs_url.clone(),
+            }),
+            WeatherProviderKind::OpenMeteo => Provider::OpenMeteo(open_meteo::OpenMeteo {
+                forecast_url: config.open_meteo_url.clone(),
+                archive_url: config.open_meteo_archive_url.clone(),
+            }),
+        }
+    }
+}
+
+impl WeatherProvider for Provider {
+    async fn fetch_forecast(&self, site: &str) -> Result<ForecastRun> {
+        match self {
+            Provider::Fmi(p) => p.fetch_forecast(site).await,
+            Provider::OpenMeteo(p) => p.fetch_forecast(site).await,
+        }
+    }
+
+    async fn fetch_observations(
+        &self,
+        site: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<ForecastPoint>> {
+        match self {
+            Provider::Fmi(p) => p.fetch_observations(site, start, end).await,
+            Provider::OpenMeteo(p) => p.fetch_observations(site, start, end).await,
+        }
+    }
+
+    fn max_observation_span(&self) -> Duration {
+        match self {
+            Provider::Fmi(p) => p.max_observation_span(),
+            Provider::OpenMeteo(p) => p.max_observation_span(),
+        }
+    }
+}
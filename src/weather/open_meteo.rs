@@ -0,0 +1,321 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use super::{ForecastPoint, ForecastRun, WeatherProvider};
+
+pub const DEFAULT_FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+pub const DEFAULT_ARCHIVE_URL: &str = "https://archive-api.open-meteo.com/v1/archive";
+
+/// How far back the forecast API serves past days; older history comes from
+/// the archive API, which in turn lags a few days behind.
+const FORECAST_PAST_DAYS: i64 = 90;
+
+const HOURLY_PARAMS: &str = "temperature_2m,relative_humidity_2m,precipitation,wind_speed_10m,wind_direction_10m,cloud_cover,pressure_msl,dew_point_2m,wind_gusts_10m,weather_code";
+
+/// Open-Meteo forecast and historical weather APIs. Sites are `lat,lon`
+/// pairs.
+#[derive(Debug, Clone)]
+pub struct OpenMeteo {
+    pub forecast_url: String,
+    pub archive_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    hourly: Hourly,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f64>>,
+    relative_humidity_2m: Vec<Option<f64>>,
+    precipitation: Vec<Option<f64>>,
+    wind_speed_10m: Vec<Option<f64>>,
+    wind_direction_10m: Vec<Option<f64>>,
+    cloud_cover: Vec<Option<f64>>,
+    pressure_msl: Vec<Option<f64>>,
+    dew_point_2m: Vec<Option<f64>>,
+    wind_gusts_10m: Vec<Option<f64>>,
+    weather_code: Vec<Option<f64>>,
+}
+
+impl WeatherProvider for OpenMeteo {
+    async fn fetch_forecast(&self, site: &str) -> Result<ForecastRun> {
+        let now = Utc::now();
+        let points = self
+            .fetch_hourly(&self.forecast_url, site, "past_days=0&forecast_days=7")
+            .await?;
+        let hour_ts = now.timestamp() - (now.timestamp() % 3600);
+        // Open-Meteo doesn't expose the model run time; the fetch hour is
+        // used instead so there's at most one stored run per hour.
+        let issued_at = DateTime::from_timestamp(hour_ts, 0).unwrap();
+        Ok(ForecastRun {
+            issued_at,
+            place_name: None,
+            points: points
+                .into_iter()
+                .filter(|p| p.timestamp >= issued_at)
+                .collect(),
+        })
+    }
+
+    /// Open-Meteo has no station observations; its model analysis for the
+    /// requested days stands in for them. Days the forecast API no longer
+    /// serves are read from the archive.
+    async fn fetch_observations(
+        &self,
+        site: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<ForecastPoint>> {
+        let range = |from: DateTime<Utc>, to: DateTime<Utc>| {
+            format!(
+                "start_date={}&end_date={}",
+                from.format("%Y-%m-%d"),
+                to.format("%Y-%m-%d")
+            )
+        };
+        let recent = (Utc::now() - Duration::days(FORECAST_PAST_DAYS))
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        let mut points = Vec::new();
+        if start < recent {
+            let to = end.min(recent - Duration::days(1));
+            points.extend(
+                self.fetch_hourly(&self.archive_url, site, &range(start, to))
+                    .await?,
+            );
+        }
+        if end >= recent {
+            let from = start.max(recent);
+            points.extend(
+                self.fetch_hourly(&self.forecast_url, site, &range(from, end))
+                    .await?,
+            );
+        }
+        let end = end.min(Utc::now());
+        Ok(points
+            .into_iter()
+            .filter(|p| p.timestamp >= start && p.timestamp <= end)
+            .collect())
+    }
+
+    fn max_observation_span(&self) -> Duration {
+        Duration::days(31)
+    }
+}
+
+impl OpenMeteo {
+    /// `range` selects the days, either `past_days=&forecast_days=` or
+    /// `start_date=&end_date=`. `url` is the forecast or the archive API,
+    /// which take the same parameters.
+    async fn fetch_hourly(&self, url: &str, site: &str, range: &str) -> Result<Vec<ForecastPoint>> {
+        let (lat, lon) = parse_latlon(site)?;
+        let client = reqwest::Client::builder().use_rustls_tls().build()?;
+
+        let url = format!(
+            "{}?latitude={}&longitude={}&hourly={}&wind_speed_unit=ms&timeformat=unixtime&{}",
+            url, lat, lon, HOURLY_PARAMS, range
+        );
+
+        tracing::debug!("Open-Meteo request: {url}");
+        let resp = client.get(&url).send().await?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("Open-Meteo API returned status {}", resp.status()));
+        }
+        let body: ForecastResponse = resp.json().await?;
+        parse_hourly(body.hourly)
+    }
+}
+
+fn parse_hourly(hourly: Hourly) -> Result<Vec<ForecastPoint>> {
+    let value = |v: &[Option<f64>], i: usize| v.get(i).copied().flatten().unwrap_or(f64::NAN);
+
+    let mut points = Vec::with_capacity(hourly.time.len());
+    for (i, &epoch) in hourly.time.iter().enumerate() {
+        let temperature_c = value(&hourly.temperature_2m, i);
+        if temperature_c.is_nan() {
+            continue;
+        }
+
+        let timestamp = DateTime::from_timestamp(epoch, 0)
+            .ok_or_else(|| anyhow!("Invalid timestamp {epoch}"))?;
+
+        points.push(ForecastPoint {
+            timestamp,
+            temperature_c,
+            wind_speed_ms: value(&hourly.wind_speed_10m, i),
+            precipitation_mm: value(&hourly.precipitation, i),
+            humidity: value(&hourly.relative_humidity_2m, i),
+            wind_direction: value(&hourly.wind_direction_10m, i),
+            cloud_cover: value(&hourly.cloud_cover, i),
+            pressure_hpa: value(&hourly.pressure_msl, i),
+            dew_point_c: value(&hourly.dew_point_2m, i),
+            wind_gust_ms: value(&hourly.wind_gusts_10m, i),
+            weather_symbol: wmo_to_symbol3(value(&hourly.weather_code, i)),
+        });
+    }
+
+    tracing::trace!("Parsed {} Open-Meteo points", points.len());
+    Ok(points)
+}
+
+fn parse_latlon(site: &str) -> Result<(f64, f64)> {
+    let (lat, lon) = site
+        .split_once(',')
+        .ok_or_else(|| anyhow!("Open-Meteo site must be `lat,lon`, got {site:?}"))?;
+    let lat = lat.trim().parse().context("Invalid latitude")?;
+    let lon = lon.trim().parse().context("Invalid longitude")?;
+    Ok((lat, lon))
+}
+
+/// Map a WMO weather interpretation code onto the closest FMI WeatherSymbol3.
+fn wmo_to_symbol3(code: f64) -> f64 {
+    if !code.is_finite() {
+        return f64::NAN;
+    }
+    match code as i64 {
+        0 | 1 => 1.0,
+        2 => 2.0,
+        3 => 3.0,
+        45 | 48 => 91.0,
+        51 | 56 | 61 => 31.0,
+        53 | 63 | 66 => 32.0,
+        55 | 57 | 65 | 67 => 33.0,
+        71 | 77 => 51.0,
+        73 => 52.0,
+        75 => 53.0,
+        80 => 21.0,
+        81 => 22.0,
+        82 => 23.0,
+        85 => 41.0,
+        86 => 43.0,
+        95 => 61.0,
+        96 | 99 => 63.0,
+        _ => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{extract::RawQuery, routing::get, Router};
+
+    use super::*;
+
+    const HOURLY: &str = include_str!("testdata/open_meteo_hourly.json");
+
+    #[test]
+    fn parses_hourly_columns() {
+        let body: ForecastResponse = serde_json::from_str(HOURLY).unwrap();
+        let points = parse_hourly(body.hourly).unwrap();
+        // The hour without a temperature is dropped
+        assert_eq!(points.len(), 2);
+
+        let first = &points[0];
+        assert_eq!(first.timestamp.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(first.temperature_c, -4.2);
+        assert_eq!(first.humidity, 91.0);
+        assert_eq!(first.precipitation_mm, 0.1);
+        assert_eq!(first.wind_speed_ms, 3.4);
+        assert_eq!(first.wind_direction, 200.0);
+        assert_eq!(first.cloud_cover, 100.0);
+        assert_eq!(first.pressure_hpa, 1003.2);
+        assert_eq!(first.dew_point_c, -5.5);
+        assert_eq!(first.wind_gust_ms, 7.9);
+        // Slight snow
+        assert_eq!(first.weather_symbol, 51.0);
+
+        let last = &points[1];
+        assert_eq!(last.temperature_c, -5.1);
+        assert!(last.wind_gust_ms.is_nan());
+        assert_eq!(last.weather_symbol, 2.0);
+    }
+
+    /// Serve the fixture on both APIs, recording each request as
+    /// `path?query`.
+    async fn stub() -> (OpenMeteo, Arc<Mutex<Vec<String>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let record = |path: &'static str| {
+            let requests = requests.clone();
+            get(move |RawQuery(query): RawQuery| async move {
+                requests
+                    .lock()
+                    .unwrap()
+                    .push(format!("{path}?{}", query.unwrap_or_default()));
+                HOURLY
+            })
+        };
+        let app = Router::new()
+            .route("/v1/forecast", record("/v1/forecast"))
+            .route("/v1/archive", record("/v1/archive"));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let provider = OpenMeteo {
+            forecast_url: format!("{base}/v1/forecast"),
+            archive_url: format!("{base}/v1/archive"),
+        };
+        (provider, requests)
+    }
+
+    #[tokio::test]
+    async fn old_observations_come_from_the_archive() {
+        let (provider, requests) = stub().await;
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .to_utc();
+        let points = provider
+            .fetch_observations("60.16,24.94", start, start + Duration::hours(3))
+            .await
+            .unwrap();
+        assert_eq!(points.len(), 2);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/v1/archive?latitude=60.16&longitude=24.94&"));
+        assert!(requests[0].contains("&wind_speed_unit=ms&timeformat=unixtime&"));
+        assert!(requests[0].ends_with("&start_date=2024-01-01&end_date=2024-01-01"));
+    }
+
+    #[tokio::test]
+    async fn recent_observations_come_from_the_forecast_api() {
+        let (provider, requests) = stub().await;
+        let end = Utc::now();
+        provider
+            .fetch_observations("60.16,24.94", end - Duration::days(2), end)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/v1/forecast?"));
+        assert!(requests[0].ends_with(&format!(
+            "&start_date={}&end_date={}",
+            (end - Duration::days(2)).format("%Y-%m-%d"),
+            end.format("%Y-%m-%d")
+        )));
+    }
+
+    #[tokio::test]
+    async fn a_range_across_the_cutoff_uses_both() {
+        let (provider, requests) = stub().await;
+        let end = Utc::now();
+        provider
+            .fetch_observations("60.16,24.94", end - Duration::days(100), end)
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let paths: Vec<&str> = requests
+            .iter()
+            .map(|r| r.split('?').next().unwrap())
+            .collect();
+        assert_eq!(paths, ["/v1/archive", "/v1/forecast"]);
+    }
+}
@@ -19,14 +19,29 @@ self.addEventListener("push", (event) => {
     }
   }
 
+  // Structured payloads carry an optional action and tag alongside the text
+  let action = null;
+  let tag = "weather";
+  if (text.startsWith("{")) {
+    try {
+      const payload = JSON.parse(text);
+      text = payload.body;
+      action = payload.action || null;
+      tag = payload.tag || tag;
+    } catch (e) {
+      // not JSON after all, show as is
+    }
+  }
+
   const options = {
     body: text,
     icon: "/static/icon-192.png",
     badge: "/static/icon-192.png",
-    tag: "weather",
+    tag,
     renotify: true,
     requireInteraction: false,
-    data: { url: "/" },
+    data: { url: "/", action },
+    actions: action ? [{ action: "apply-radiator", title: action.title }] : [],
   };
 
   event.waitUntil(
@@ -38,6 +53,20 @@ self.addEventListener("push", (event) => {
 
 self.addEventListener("notificationclick", (event) => {
   event.notification.close();
+  const data = event.notification.data || {};
+  if (event.action === "apply-radiator" && data.action) {
+    event.waitUntil(
+      fetch("/radiator", {
+        method: "POST",
+        body: new URLSearchParams({
+          radiator: String(data.action.radiator),
+          radiator_id: String(data.action.radiator_id || 1),
+          source: "notification",
+        }),
+      }).catch((err) => console.error("[SW] applying radiator setting failed:", err)),
+    );
+    return;
+  }
   const url = (event.notification.data && event.notification.data.url) || "/";
   event.waitUntil(
     clients